@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -10,75 +11,201 @@ use clap::{Parser, Subcommand, ValueEnum};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Append a JSON-lines trace of every git invocation to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<std::path::PathBuf>,
+
+    /// Show git's raw error output next to workz's explanation
+    #[arg(long, short, global = true)]
+    pub verbose: bool,
+
+    /// Hide hook output unless the hook fails
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+
+    /// Always show hook output, even with --quiet
+    #[arg(long, global = true)]
+    pub show_hooks_output: bool,
+}
+
+impl Cli {
+    /// The global flags as given, for passing on to a child `workz` run.
+    pub fn global_args(&self) -> Vec<std::ffi::OsString> {
+        let mut args = Vec::new();
+        if let Some(path) = &self.log_file {
+            args.push("--log-file".into());
+            args.push(path.into());
+        }
+        if self.verbose {
+            args.push("--verbose".into());
+        }
+        if self.quiet {
+            args.push("--quiet".into());
+        }
+        if self.show_hooks_output {
+            args.push("--show-hooks-output".into());
+        }
+        args
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Create a new worktree with automatic dependency syncing
-    Start {
-        /// Branch name (created if it doesn't exist)
-        branch: String,
+    Start(StartArgs),
 
-        /// Base branch to create from (defaults to current HEAD)
-        #[arg(short, long)]
-        base: Option<String>,
+    /// Like `start`, but print the worktree's path last instead of cd-ing into
+    /// it — for scripts, no shell integration assumed
+    New(StartArgs),
 
-        /// Skip symlink and copy operations
+    /// List all worktrees with status
+    #[command(alias = "ls")]
+    List {
+        /// Include worktrees hidden by a .workzignore marker
         #[arg(long)]
-        no_sync: bool,
+        all: bool,
 
-        /// Launch an AI coding tool in the new worktree
-        #[arg(short, long)]
-        ai: bool,
+        /// Draw size bars and color last-commit age (terminal only, honours NO_COLOR)
+        #[arg(long)]
+        bars: bool,
 
-        /// AI tool to launch
-        #[arg(long, default_value = "claude", value_enum)]
-        ai_tool: AiTool,
+        /// Keep running and reprint when a worktree's HEAD or dirty state changes
+        #[arg(long, short)]
+        watch: bool,
 
-        /// Run docker/podman compose up in the new worktree
+        /// Group worktrees under their detected project type (Node, Rust, ...)
         #[arg(long)]
-        docker: bool,
+        by_project_type: bool,
 
-        /// Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME and write .env.local
+        /// Don't measure worktree sizes (faster with many large worktrees)
         #[arg(long)]
-        isolated: bool,
-    },
+        no_size: bool,
 
-    /// List all worktrees with status
-    #[command(alias = "ls")]
-    List,
+        /// Measure full recursive sizes (slower); symlinked dirs like shared
+        /// node_modules aren't counted. Default: top-level files only
+        #[arg(long, conflicts_with = "no_size")]
+        sizes: bool,
+    },
 
     /// Fuzzy-switch to a worktree (zoxide-style)
     #[command(alias = "s")]
     Switch {
         /// Fuzzy search query
         query: Option<String>,
+
+        /// Create the worktree (like `start`) if no worktree has this exact branch
+        #[arg(short, long, requires = "query")]
+        create: bool,
+
+        /// Include worktrees hidden by a .workzignore marker
+        #[arg(long)]
+        all: bool,
+
+        /// Picker height in lines or percent, e.g. 20 or 60% (see [switch] height)
+        #[arg(long, value_name = "HEIGHT")]
+        height: Option<String>,
+
+        /// Preview command for the highlighted worktree; `{2}` is its path
+        #[arg(long, value_name = "CMD")]
+        preview: Option<String>,
+
+        /// Match the query exactly instead of fuzzily
+        #[arg(long, conflicts_with = "regex")]
+        exact: bool,
+
+        /// Treat the query as a regular expression
+        #[arg(long)]
+        regex: bool,
+
+        /// Skip the interactive picker: go to the best match for the query
+        /// (automatic when there is no terminal)
+        #[arg(long)]
+        no_tui: bool,
+
+        /// Select several worktrees (Tab) and open each in --editor; the shell
+        /// cds into the first
+        #[arg(long, conflicts_with_all = ["create", "no_tui"])]
+        multi: bool,
+
+        /// Editor --multi opens the worktrees in
+        #[arg(long, value_enum, default_value = "code", requires = "multi")]
+        editor: AiTool,
     },
 
-    /// Remove a worktree and clean up
-    Done {
-        /// Branch name of worktree to remove (defaults to current)
-        branch: Option<String>,
+    /// List worktrees by frecency: how often and how recently you cd'd into them
+    Recent,
 
-        /// Force removal even with uncommitted changes
-        #[arg(short, long)]
-        force: bool,
+    /// Print the path of a branch's worktree; exits non-zero if there is none
+    Which {
+        /// Branch name to look up
+        branch: String,
+    },
 
-        /// Also delete the branch after removal
-        #[arg(short, long)]
-        delete_branch: bool,
+    /// Show which worktrees have uncommitted changes to the given files
+    Owners {
+        /// Files to look up (relative to the current directory, or absolute)
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
 
-        /// Drop the database created by --isolated
+    /// Show how two worktrees differ (branch tips, or working trees with --working)
+    Diff {
+        /// Branch (or `start --name`) of the first worktree
+        a: String,
+
+        /// Second worktree (default: the current one)
+        b: Option<String>,
+
+        /// Compare working trees, uncommitted changes to tracked files included
         #[arg(long)]
-        cleanup_db: bool,
+        working: bool,
+    },
+
+    /// Move a worktree to another directory (e.g. another drive), then resync it
+    Move {
+        /// Branch (or `start --name`) of the worktree to move
+        branch: String,
+
+        /// Where to put it; must not exist yet
+        new_path: PathBuf,
     },
 
+    /// Lock a worktree so git keeps it while its directory is unavailable
+    /// (e.g. on an unmounted drive); `done` and `move` refuse until unlocked
+    Lock {
+        /// Branch (or `start --name`) of the worktree to lock
+        branch: String,
+
+        /// Why it's locked, shown by `git worktree list`
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Unlock a worktree locked with `lock`
+    Unlock {
+        /// Branch (or `start --name`) of the worktree to unlock
+        branch: String,
+    },
+
+    /// Remove a worktree and clean up
+    Done(DoneArgs),
+
     /// Sync symlinks, env files, and deps into the current worktree
     Sync,
 
+    /// Bring a worktree made with plain `git worktree add` under workz management
+    Adopt {
+        /// Path of the existing worktree
+        path: PathBuf,
+    },
+
     /// Show rich status of all worktrees
     Status,
 
+    /// Report disk used by all worktrees and how much symlinking saves
+    Stats,
+
     /// Prune orphaned worktrees
     Clean {
         /// Also remove worktrees whose branches are already merged into base
@@ -88,6 +215,27 @@ pub enum Commands {
         /// Base branch to check merged status against (defaults to main or master)
         #[arg(long)]
         base: Option<String>,
+
+        /// List what would be pruned or removed without touching anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Live view of worktree state: dirty, ahead/behind, docker (q to quit)
+    Top {
+        /// Seconds between refreshes
+        #[arg(short = 'n', long, default_value_t = 2, value_name = "SECS")]
+        interval: u64,
+    },
+
+    /// Fetch all remotes (or one) and prune deleted remote branches
+    Fetch {
+        /// Remote to fetch (defaults to all remotes)
+        remote: Option<String>,
+
+        /// Keep remote-tracking refs whose upstream branch was deleted
+        #[arg(long)]
+        no_prune: bool,
     },
 
     /// Run parallel AI agents across multiple worktrees
@@ -96,6 +244,18 @@ pub enum Commands {
         cmd: FleetCmd,
     },
 
+    /// Manage the project's .workz.toml
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCmd,
+    },
+
+    /// Inspect configured hooks
+    Hooks {
+        #[command(subcommand)]
+        cmd: HooksCmd,
+    },
+
     /// Start a local web dashboard at localhost:PORT
     Serve {
         /// Port to listen on
@@ -108,16 +268,237 @@ pub enum Commands {
     },
 
     /// Start an MCP server exposing workz tools to AI agents (stdio transport)
-    Mcp,
+    Mcp {
+        /// Reject incoming messages larger than this many bytes
+        #[arg(long, default_value_t = crate::mcp::DEFAULT_MAX_MESSAGE_BYTES)]
+        max_message_bytes: usize,
+
+        /// Exit after this many seconds without a message (default: never)
+        #[arg(long, value_name = "SECS")]
+        idle_timeout: Option<u64>,
+    },
+
+    /// Print the workz version
+    Version {
+        /// Also ask GitHub whether a newer release exists (needs network, 3s timeout)
+        #[arg(long)]
+        check: bool,
+    },
 
     /// Print shell integration script
     Init {
         /// Shell to generate integration for
         #[arg(value_enum)]
         shell: Shell,
+
+        /// Print only the tab completions (e.g. when the wrapper comes from elsewhere)
+        #[arg(long, conflicts_with = "wrapper_only")]
+        completions_only: bool,
+
+        /// Print only the cd wrapper function, for completions managed separately
+        #[arg(long)]
+        wrapper_only: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCmd {
+    /// Write a .workz.toml into the main checkout
+    Init {
+        /// Copy the .workz.toml of another repo (directory or file path)
+        #[arg(long, value_name = "REPO")]
+        mirror_config: Option<PathBuf>,
+
+        /// Overwrite an existing .workz.toml
+        #[arg(short, long)]
+        force: bool,
     },
 }
 
+#[derive(Subcommand)]
+pub enum HooksCmd {
+    /// Show every hook that will run (all config layers), where it runs, and
+    /// which file it comes from
+    List,
+}
+
+#[derive(Args)]
+pub struct DoneArgs {
+    /// Branch name of worktree to remove (defaults to current)
+    pub branch: Option<String>,
+
+    /// Force removal even with uncommitted changes
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Also delete the branch after removal
+    #[arg(short, long)]
+    pub delete_branch: bool,
+
+    /// Drop the database created by --isolated
+    #[arg(long)]
+    pub cleanup_db: bool,
+
+    /// Also delete the branch on its upstream remote (implies --delete-branch)
+    #[arg(long)]
+    pub prune_remote: bool,
+
+    /// Remove every clean, merged, fully pushed worktree (never the main one)
+    #[arg(long, conflicts_with_all = ["branch", "force"])]
+    pub all: bool,
+
+    /// With --all, remove up to N worktrees at once
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "all")]
+    pub parallel: usize,
+
+    /// Don't ask for confirmation (unmerged or remote branch deletes, --all)
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Default)]
+pub struct StartArgs {
+    /// Branch name (created if it doesn't exist; defaults to pr-<N> with --from-pr)
+    #[arg(required_unless_present = "from_pr")]
+    pub branch: Option<String>,
+
+    /// Directory suffix to use instead of the branch (`<repo>--<NAME>`);
+    /// `done`/`switch -c` accept the name in place of the branch
+    #[arg(long, value_name = "NAME")]
+    pub name: Option<String>,
+
+    /// Base branch to create from (defaults to current HEAD)
+    #[arg(short, long)]
+    pub base: Option<String>,
+
+    /// Fetch and branch from the remote default branch, e.g. origin/main
+    /// (see worktree.base_remote)
+    #[arg(long, conflicts_with = "base")]
+    pub base_remote: bool,
+
+    /// Never set an upstream for the new branch, even when --base is a remote
+    /// ref like origin/main (--base-remote already implies this)
+    #[arg(long)]
+    pub no_track: bool,
+
+    /// Branch off the current HEAD of another worktree (for stacking branches)
+    #[arg(long, value_name = "WORKTREE", conflicts_with_all = ["base", "base_remote"])]
+    pub from: Option<String>,
+
+    /// Branch off a release tag (e.g. for hotfixes); fails if the tag doesn't exist
+    #[arg(long, value_name = "TAG", conflicts_with_all = ["base", "base_remote", "from"])]
+    pub from_tag: Option<String>,
+
+    /// Check out GitHub pull request N: fetch its head into a local branch
+    /// (pr-N unless BRANCH is given) from [remotes] pull or the default
+    /// branch's remote
+    #[arg(long, value_name = "N", conflicts_with_all = ["base", "base_remote", "from", "from_tag", "reset_base"])]
+    pub from_pr: Option<u32>,
+
+    /// Skip symlink and copy operations
+    #[arg(long)]
+    pub no_sync: bool,
+
+    /// Launch an AI coding tool in the new worktree
+    #[arg(short, long)]
+    pub ai: bool,
+
+    /// AI tool to launch
+    #[arg(long, default_value = "claude", value_enum)]
+    pub ai_tool: AiTool,
+
+    /// Fully detach the launched tool: no shared stdio, own process group, so
+    /// it outlives the terminal (default for GUI editors: cursor, code, windsurf)
+    #[arg(long)]
+    pub detach_editor: bool,
+
+    /// Open the worktree in a tmux window named after the branch (a new
+    /// detached session outside tmux; see worktree.open_in_tmux)
+    #[arg(long)]
+    pub open_in_tmux: bool,
+
+    /// Run docker/podman compose up in the new worktree
+    #[arg(long)]
+    pub docker: bool,
+
+    /// Rebuild images before starting containers (implies --docker)
+    #[arg(long)]
+    pub docker_build: bool,
+
+    /// Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME and write .env.local
+    #[arg(long)]
+    pub isolated: bool,
+
+    /// Copy all untracked files from the current worktree into the new one
+    #[arg(long)]
+    pub copy_untracked: bool,
+
+    /// Clone every git-ignored file from the current worktree into the new one
+    /// (symlink dirs are linked, the rest copied up to 1 GiB)
+    #[arg(long)]
+    pub clone_ignored: bool,
+
+    /// Open an interactive $SHELL in the new worktree instead of cd-ing
+    /// (works without shell integration)
+    #[arg(long)]
+    pub shell: bool,
+
+    /// Initialize submodules in the new worktree (see worktree.init_submodules)
+    #[arg(long)]
+    pub submodules: bool,
+
+    /// Run this instead of the detected install command (via `sh -c`)
+    #[arg(long, value_name = "CMD")]
+    pub install_cmd: Option<String>,
+
+    /// Cap the package manager's install concurrency (see sync.install_jobs)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+
+    /// Hide package-manager output unless the install fails (see sync.quiet_install)
+    #[arg(long)]
+    pub quiet_install: bool,
+
+    /// If the target directory exists but isn't a worktree (e.g. after an
+    /// interrupted run), delete it and create the worktree there
+    #[arg(long)]
+    pub reuse: bool,
+
+    /// If the branch already exists, reset it to the base tip (--base, --from,
+    /// --from-tag, else HEAD), discarding its commits; asks first
+    #[arg(long, conflicts_with = "base_remote")]
+    pub reset_base: bool,
+
+    /// Don't ask before --reset-base discards commits
+    #[arg(long, requires = "reset_base")]
+    pub force: bool,
+
+    /// If setup fails after the worktree is created (sync, install, submodules,
+    /// isolation, docker), remove it again — and the branch, if it was new.
+    /// Warnings such as a skipped symlink or a failing post_start hook don't count
+    #[arg(long)]
+    pub rollback_on_error: bool,
+
+    /// Use the branch name exactly as given, skipping worktree.branch_template
+    #[arg(long)]
+    pub no_template: bool,
+
+    /// Set KEY=VAL in the post_start hook's environment (repeatable)
+    #[arg(long = "env", value_name = "KEY=VAL", value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+}
+
+/// Parse `KEY=VAL`; the key must be a valid shell variable name.
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s.split_once('=').ok_or_else(|| format!("expected KEY=VAL, got '{s}'"))?;
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("'{key}' is not a valid variable name (letters, digits, _)"));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 #[derive(Clone, ValueEnum)]
 pub enum Shell {
     Zsh,
@@ -125,8 +506,9 @@ pub enum Shell {
     Fish,
 }
 
-#[derive(Clone, ValueEnum)]
+#[derive(Clone, Default, ValueEnum)]
 pub enum AiTool {
+    #[default]
     Claude,
     Cursor,
     Code,
@@ -165,6 +547,10 @@ pub enum FleetCmd {
         /// Command to execute (e.g. "cargo test")
         #[arg(required = true, trailing_var_arg = true)]
         cmd: Vec<String>,
+
+        /// Run in at most N worktrees at once (default: number of CPUs)
+        #[arg(long, value_name = "N")]
+        parallel: Option<usize>,
     },
 
     /// Remove all fleet worktrees and clean up
@@ -205,6 +591,13 @@ pub enum FleetCmd {
     },
 }
 
+impl AiTool {
+    /// Editors that open their own window rather than running in the terminal.
+    pub fn is_gui(&self) -> bool {
+        matches!(self, AiTool::Cursor | AiTool::Code | AiTool::Windsurf)
+    }
+}
+
 impl std::fmt::Display for AiTool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -218,3 +611,25 @@ impl std::fmt::Display for AiTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_var_needs_key_and_equals() {
+        assert_eq!(parse_env_var("SEED_DATA=1"), Ok(("SEED_DATA".into(), "1".into())));
+        assert_eq!(parse_env_var("URL=a=b"), Ok(("URL".into(), "a=b".into())));
+        assert_eq!(parse_env_var("EMPTY="), Ok(("EMPTY".into(), String::new())));
+        for bad in ["NOVALUE", "=1", "1X=1", "MY-VAR=1"] {
+            assert!(parse_env_var(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn global_args_round_trip() {
+        let cli = Cli::parse_from(["workz", "done", "--all", "-q", "--log-file", "trace.log", "--show-hooks-output"]);
+        assert_eq!(cli.global_args(), ["--log-file", "trace.log", "--quiet", "--show-hooks-output"]);
+        assert!(Cli::parse_from(["workz", "done", "--all"]).global_args().is_empty());
+    }
+}
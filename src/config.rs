@@ -1,8 +1,11 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::git;
 
 const CONFIG_FILE: &str = ".workz.toml";
+const LOCAL_CONFIG_FILE: &str = ".workz.local.toml";
 
 #[derive(Debug, Default, Deserialize)]
 pub struct Config {
@@ -12,6 +15,51 @@ pub struct Config {
     pub hooks: HooksConfig,
     #[serde(default)]
     pub isolation: IsolationConfig,
+    #[serde(default)]
+    pub list: ListConfig,
+    #[serde(default)]
+    pub worktree: WorktreeConfig,
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+
+    #[serde(default)]
+    pub done: DoneConfig,
+
+    #[serde(default)]
+    pub switch: SwitchConfig,
+
+    #[serde(default)]
+    pub remotes: RemotesConfig,
+
+    /// `[[git_config]]` rules; the first whose pattern matches a new
+    /// worktree's branch is applied to that worktree only
+    #[serde(default)]
+    pub git_config: Vec<GitConfigRule>,
+}
+
+/// Git config applied to new worktrees whose branch matches `pattern`, e.g.
+/// `pattern = "work/*"`, `set = { "user.email" = "me@corp.com" }`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitConfigRule {
+    /// Branch glob (`*` also matches `/`)
+    pub pattern: String,
+    /// git config keys to values
+    #[serde(default)]
+    pub set: std::collections::BTreeMap<String, String>,
+}
+
+impl Config {
+    /// The first `[[git_config]]` rule matching `branch`. Invalid patterns
+    /// are reported and skipped.
+    pub fn git_config_for(&self, branch: &str) -> Option<&GitConfigRule> {
+        self.git_config.iter().find(|rule| match glob::Pattern::new(&rule.pattern) {
+            Ok(pattern) => pattern.matches(branch),
+            Err(e) => {
+                eprintln!("  warning: invalid git_config pattern '{}': {}", rule.pattern, e);
+                false
+            }
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,24 +68,297 @@ pub struct SyncConfig {
     #[serde(default = "default_symlink_dirs")]
     pub symlink: Vec<String>,
 
-    /// File patterns to copy into worktrees
+    /// File patterns to copy into worktrees (repo-relative, absolute, or `~/`)
     #[serde(default = "default_copy_patterns")]
     pub copy: Vec<String>,
 
-    /// Patterns to never touch
+    /// Patterns to never touch (globs, matched against repo-relative paths when copying)
     #[serde(default)]
     pub ignore: Vec<String>,
+
+    /// Detect the package manager and install dependencies (default: true).
+    /// Turn off to leave bootstrapping to a post_start hook.
+    #[serde(default = "default_true")]
+    pub auto_install: bool,
+
+    /// If a frozen-lockfile install fails (lockfile out of date), retry it as a
+    /// plain install instead of leaving the worktree without deps (default: false).
+    #[serde(default)]
+    pub install_fallback: bool,
+
+    /// Shell command that replaces the detected install (e.g. `make setup`),
+    /// run via `sh -c` in the worktree. `start --install-cmd` overrides it.
+    #[serde(default)]
+    pub install_cmd: Option<String>,
+    /// Run `mise install` / `asdf install` when the worktree pins tool versions
+    /// (`.mise.toml`, `.tool-versions`), before installing deps (default: false).
+    #[serde(default)]
+    pub install_tool_versions: bool,
+
+    /// Only copy `copy` matches that git ignores; tracked files already come
+    /// with the checkout (default: false).
+    #[serde(default)]
+    pub copy_gitignored_only: bool,
+
+    /// Cap the package manager's own concurrency (npm sockets, yarn/pnpm/bun
+    /// network concurrency, uv/poetry workers, CARGO_BUILD_JOBS).
+    /// `start --jobs` overrides it. Must be at least 1, like `--jobs`.
+    #[serde(default)]
+    pub install_jobs: Option<std::num::NonZeroUsize>,
+
+    /// When a source directory to symlink is itself a symlink (pnpm store,
+    /// hoisted deps), link to its real path instead of chaining links (default: false).
+    #[serde(default)]
+    pub resolve_source_symlinks: bool,
+
+    /// When the source checkout has no node_modules / .venv yet, install there
+    /// first so every worktree symlinks the same deps (default: false).
+    #[serde(default)]
+    pub install_in_source: bool,
+
+    /// Capture install output and show it only if the install fails; success
+    /// is one line with the time taken (default: false). `start --quiet-install` sets it.
+    #[serde(default)]
+    pub quiet_install: bool,
 }
 
+fn default_true() -> bool { true }
+
 #[derive(Debug, Default, Deserialize)]
 pub struct HooksConfig {
-    /// Shell command to run after worktree creation
+    /// Shell command(s) to run from the main repo before a worktree is created
+    /// (e.g. `git fetch --all`)
+    #[serde(default)]
+    pub pre_start: Option<Hook>,
+
+    /// Shell command(s) to run in the worktree after each completed sync
+    /// (`start` without --no-sync, `sync`, `adopt`)
     #[serde(default)]
-    pub post_start: Option<String>,
+    pub post_sync: Option<Hook>,
 
-    /// Shell command to run before worktree removal
+    /// Shell command(s) to run after worktree creation
     #[serde(default)]
-    pub pre_done: Option<String>,
+    pub post_start: Option<Hook>,
+
+    /// Shell command(s) to run before worktree removal
+    #[serde(default)]
+    pub pre_done: Option<Hook>,
+
+    /// Shell command(s) to run from the main repo after a worktree is removed
+    #[serde(default)]
+    pub post_done: Option<Hook>,
+
+    /// Command(s) the shell wrapper evals in your interactive shell after
+    /// `switch` cds into a worktree (e.g. `direnv reload`)
+    #[serde(default)]
+    pub post_switch: Option<Hook>,
+}
+
+impl HooksConfig {
+    /// Every hook by config key, in the order they fire over a worktree's life.
+    pub fn all(&self) -> [(&'static str, &Option<Hook>); 6] {
+        [
+            ("pre_start", &self.pre_start),
+            ("post_sync", &self.post_sync),
+            ("post_start", &self.post_start),
+            ("post_switch", &self.post_switch),
+            ("pre_done", &self.pre_done),
+            ("post_done", &self.post_done),
+        ]
+    }
+}
+
+impl BootstrapConfig {
+    /// Every bootstrap step by project type key.
+    pub fn all(&self) -> [(&'static str, &Option<Hook>); 5] {
+        [
+            ("node", &self.node),
+            ("python", &self.python),
+            ("rust", &self.rust),
+            ("go", &self.go),
+            ("java", &self.java),
+        ]
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DoneConfig {
+    /// Regex that pulls a ticket ID out of the branch name (e.g. `[A-Z]+-\d+`),
+    /// filled into `{ticket}` in pre_done/post_done hooks. With a capture
+    /// group, the first group is the ticket.
+    #[serde(default)]
+    pub ticket_pattern: Option<String>,
+
+    /// Before removing a worktree, copy its untracked and ignored files
+    /// (minus symlinked dependency dirs) to a timestamped backup under the
+    /// state dir (`~/.local/state/workz/backups`)
+    #[serde(default)]
+    pub backup_untracked: Option<bool>,
+}
+
+impl DoneConfig {
+    /// The ticket ID in `branch`, if a pattern is set and matches.
+    pub fn ticket(&self, branch: &str) -> Option<String> {
+        let pattern = self.ticket_pattern.as_deref()?;
+        let re = match regex::Regex::new(pattern) {
+            Ok(re) => re,
+            Err(e) => {
+                eprintln!("  warning: invalid done.ticket_pattern: {}", e);
+                return None;
+            }
+        };
+        let caps = re.captures(branch)?;
+        let ticket = caps.get(1).or_else(|| caps.get(0))?;
+        Some(ticket.as_str().to_string())
+    }
+}
+
+/// Remotes for fork-based (triangular) workflows: base new work on `pull`
+/// (e.g. `upstream`), push branches to `push` (e.g. `fork`).
+#[derive(Debug, Default, Deserialize)]
+pub struct RemotesConfig {
+    /// Remote to push branches to and prune them from (default: origin)
+    #[serde(default)]
+    pub push: Option<String>,
+
+    /// Remote whose default branch new work starts from (default: origin,
+    /// or the only remote there is)
+    #[serde(default)]
+    pub pull: Option<String>,
+}
+
+impl RemotesConfig {
+    pub fn push(&self) -> &str {
+        self.push.as_deref().unwrap_or("origin")
+    }
+
+    pub fn pull(&self) -> Result<String> {
+        match &self.pull {
+            Some(remote) => Ok(remote.clone()),
+            None => git::primary_remote(),
+        }
+    }
+}
+
+/// Options for the `switch` picker. Unset fields keep the built-in layout:
+/// 40% of the terminal, prompt on top, fuzzy matching, no preview.
+#[derive(Debug, Default, Deserialize)]
+pub struct SwitchConfig {
+    /// Picker height: lines (`20`) or a percentage of the terminal (`60%`)
+    #[serde(default)]
+    pub height: Option<String>,
+
+    /// Preview command; `{2}` is the worktree path (e.g. `git -C {2} log --oneline -10`)
+    #[serde(default)]
+    pub preview: Option<String>,
+
+    /// List top-down with the prompt above it (default: true)
+    #[serde(default)]
+    pub reverse: Option<bool>,
+
+    /// How the query matches: `fuzzy` (default), `exact`, or `regex`
+    #[serde(default, rename = "match", deserialize_with = "lenient_match_mode")]
+    pub match_mode: Option<MatchMode>,
+}
+
+/// An unknown `match` value is warned about and ignored, so a typo doesn't
+/// throw away the rest of the file.
+fn lenient_match_mode<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Option<MatchMode>, D::Error> {
+    let Some(value) = Option::<String>::deserialize(de)? else {
+        return Ok(None);
+    };
+    match value.as_str() {
+        "fuzzy" => Ok(Some(MatchMode::Fuzzy)),
+        "exact" => Ok(Some(MatchMode::Exact)),
+        "regex" => Ok(Some(MatchMode::Regex)),
+        other => {
+            eprintln!("  warning: unknown switch.match '{}' (expected fuzzy, exact or regex) — ignoring it", other);
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    Fuzzy,
+    Exact,
+    Regex,
+}
+
+impl SwitchConfig {
+    /// Fields set in `over` win; the rest come from `self`.
+    pub fn overridden_by(self, over: SwitchConfig) -> SwitchConfig {
+        SwitchConfig {
+            height: over.height.or(self.height),
+            preview: over.preview.or(self.preview),
+            reverse: over.reverse.or(self.reverse),
+            match_mode: over.match_mode.or(self.match_mode),
+        }
+    }
+
+    /// The picker height, checked up front so a typo is an error rather than a
+    /// picker that silently falls back (or a panic inside it).
+    pub fn height(&self) -> Result<&str> {
+        let Some(height) = self.height.as_deref() else {
+            return Ok("40%");
+        };
+        let valid = match height.strip_suffix('%') {
+            Some(pct) => pct.parse::<u8>().is_ok_and(|p| (1..=100).contains(&p)),
+            None => height.parse::<u16>().is_ok_and(|n| n > 0),
+        };
+        if !valid {
+            bail!("invalid switch height '{}': use lines (e.g. 20) or a percentage (e.g. 60%)", height);
+        }
+        Ok(height)
+    }
+}
+
+/// Toolchain setup per detected project type (e.g. `python = "uv venv --python 3.12"`,
+/// `node = "corepack enable"`). Runs on every sync after files are copied and
+/// before dependencies are installed, so keep the commands idempotent.
+#[derive(Debug, Default, Deserialize)]
+pub struct BootstrapConfig {
+    #[serde(default)]
+    pub node: Option<Hook>,
+    #[serde(default)]
+    pub python: Option<Hook>,
+    #[serde(default)]
+    pub rust: Option<Hook>,
+    #[serde(default)]
+    pub go: Option<Hook>,
+    #[serde(default)]
+    pub java: Option<Hook>,
+}
+
+/// A hook is one shell command or a list run in order:
+/// `post_start = "make setup"` or `post_start = ["make deps", "make db"]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Hook {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Hook {
+    pub fn commands(&self) -> &[String] {
+        match self {
+            Hook::One(cmd) => std::slice::from_ref(cmd),
+            Hook::Many(cmds) => cmds,
+        }
+    }
+}
+
+/// When both layers define a hook, run the outer (e.g. global) commands first,
+/// then the inner (e.g. project) ones.
+fn merge_hook(outer: Option<Hook>, inner: Option<Hook>) -> Option<Hook> {
+    match (outer, inner) {
+        (Some(outer), Some(inner)) => {
+            let mut cmds = outer.commands().to_vec();
+            cmds.extend_from_slice(inner.commands());
+            Some(Hook::Many(cmds))
+        }
+        (outer, inner) => inner.or(outer),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,6 +372,97 @@ pub struct IsolationConfig {
     pub base_port: u16,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ListConfig {
+    /// Worktrees containing this file are hidden from list/switch (default: .workzignore)
+    #[serde(default = "default_ignore_marker")]
+    pub ignore_marker: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WorktreeConfig {
+    /// Run `git submodule update --init --recursive` in new worktrees
+    #[serde(default)]
+    pub init_submodules: Option<bool>,
+
+    /// Create new branches from the freshly fetched remote default branch
+    #[serde(default)]
+    pub base_remote: Option<bool>,
+
+    /// Open every new worktree in its own tmux window (or pass --open-in-tmux)
+    #[serde(default)]
+    pub open_in_tmux: Option<bool>,
+
+    /// Expand what's passed to `start` into the branch name, e.g.
+    /// `"{user}/{input}"`; `{user}` is git's user.name (slugged) or `$USER`
+    #[serde(default)]
+    pub branch_template: Option<String>,
+
+    /// Key to sign commits with in new worktrees (sets user.signingkey and
+    /// commit.gpgsign for that worktree only)
+    #[serde(default)]
+    pub signing_key: Option<String>,
+
+    /// Commit message template for new worktrees: relative to the main
+    /// checkout, absolute, or `~/...`. Must exist.
+    #[serde(default)]
+    pub commit_template: Option<String>,
+}
+
+impl WorktreeConfig {
+    /// `commit_template` resolved to an absolute path, checked to exist.
+    pub fn commit_template_path(&self, main_checkout: &Path) -> Result<Option<PathBuf>> {
+        let Some(template) = self.commit_template.as_deref() else {
+            return Ok(None);
+        };
+        let path = match template.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().context("no home directory for commit_template")?.join(rest),
+            None => main_checkout.join(template),
+        };
+        if !path.is_file() {
+            bail!("worktree.commit_template '{}' not found (looked for {})", template, path.display());
+        }
+        Ok(Some(path))
+    }
+
+    /// The branch `start <input>` should create: `input` run through
+    /// `branch_template`, or unchanged when no template is set.
+    pub fn expand_branch(&self, input: &str, user: &str) -> String {
+        match &self.branch_template {
+            Some(template) => template.replace("{user}", &slug(user)).replace("{input}", input),
+            None => input.to_string(),
+        }
+    }
+}
+
+/// Lowercase, with runs of anything but letters, digits, `.`, `_` as one `-`.
+fn slug(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.trim().chars() {
+        if c.is_alphanumeric() || c == '.' || c == '_' {
+            out.extend(c.to_lowercase());
+        } else if !out.ends_with('-') && !out.is_empty() {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+fn default_ignore_marker() -> String { ".workzignore".to_string() }
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self { ignore_marker: default_ignore_marker() }
+    }
+}
+
+impl ListConfig {
+    /// Whether the worktree at `path` opted out of interactive views.
+    pub fn hides(&self, path: &Path) -> bool {
+        path.join(&self.ignore_marker).exists()
+    }
+}
+
 fn default_port_range_size() -> u16 { 10 }
 fn default_base_port() -> u16 { 3000 }
 
@@ -110,15 +522,8 @@ fn default_copy_patterns() -> Vec<String> {
         ".env.*",
         ".env*",
         ".envrc",
-        // Tool versions
+        // Tool versions (per-language ones come from project detection)
         ".tool-versions",
-        ".node-version",
-        ".python-version",
-        ".ruby-version",
-        ".nvmrc",
-        // Package manager configs (may contain local registry tokens)
-        ".npmrc",
-        ".yarnrc.yml",
         // Docker overrides
         "docker-compose.override.yml",
         "docker-compose.override.yaml",
@@ -137,6 +542,15 @@ impl Default for SyncConfig {
             symlink: default_symlink_dirs(),
             copy: default_copy_patterns(),
             ignore: Vec::new(),
+            auto_install: true,
+            install_fallback: false,
+            install_cmd: None,
+            install_tool_versions: false,
+            copy_gitignored_only: false,
+            install_jobs: None,
+            resolve_source_symlinks: false,
+            install_in_source: false,
+            quiet_install: false,
         }
     }
 }
@@ -144,9 +558,13 @@ impl Default for SyncConfig {
 
 /// Load config: global (~/.config/workz/config.toml) merged with project (.workz.toml).
 /// Project config takes priority over global config.
+///
+/// The project file is always read from the main checkout, even when `repo_root`
+/// is a linked worktree, so every worktree shares one `.workz.toml` regardless
+/// of whether its branch contains the file.
 pub fn load_config(repo_root: &Path) -> Result<Config> {
     let global = load_global_config();
-    let project = load_project_config(repo_root);
+    let project = load_project_config(&main_checkout(repo_root));
 
     match (global, project) {
         (Some(g), Some(p)) => Ok(merge_configs(g, p)),
@@ -156,23 +574,118 @@ pub fn load_config(repo_root: &Path) -> Result<Config> {
     }
 }
 
-fn load_global_config() -> Option<Config> {
-    let config_dir = dirs::config_dir()?;
-    let path = config_dir.join("workz").join("config.toml");
-    if !path.exists() {
-        return None;
+/// Load config for operations on a specific worktree.
+/// Precedence: global < project < worktree-local (`.workz.local.toml` in the
+/// worktree directory, meant to be git-ignored).
+pub fn load_worktree_config(repo_root: &Path, worktree: &Path) -> Result<Config> {
+    let config = load_config(repo_root)?;
+    match load_local_config(worktree) {
+        Some(local) => Ok(merge_local(config, local)),
+        None => Ok(config),
     }
-    let contents = std::fs::read_to_string(&path).ok()?;
-    toml::from_str(&contents).ok()
 }
 
-fn load_project_config(repo_root: &Path) -> Option<Config> {
-    let path = repo_root.join(CONFIG_FILE);
+/// Starting point written by `workz config init`; every key shows its default.
+const STARTER_CONFIG: &str = r#"# workz project config — see https://github.com/rohansx/workz#configuration
+
+[sync]
+# symlink = ["node_modules", "target", ".venv"]
+# copy = [".env*", ".envrc"]
+# ignore = []
+# auto_install = true
+
+[hooks]
+# post_start = "make setup"
+# pre_done = "make stop"
+"#;
+
+/// Write `.workz.toml` into `repo_root`: a copy of `source`'s (a repo directory
+/// or the file itself), checked to parse first, or a commented starter.
+pub fn init_project_config(repo_root: &Path, source: Option<&Path>, force: bool) -> Result<PathBuf> {
+    let dest = repo_root.join(CONFIG_FILE);
+    if dest.exists() && !force {
+        bail!("{} already exists — use --force to overwrite it", dest.display());
+    }
+
+    let contents = match source {
+        Some(source) => {
+            let file = if source.is_dir() { source.join(CONFIG_FILE) } else { source.to_path_buf() };
+            let contents = std::fs::read_to_string(&file)
+                .with_context(|| format!("could not read {}", file.display()))?;
+            toml::from_str::<Config>(&contents)
+                .with_context(|| format!("{} is not a valid workz config", file.display()))?;
+            contents
+        }
+        None => STARTER_CONFIG.to_string(),
+    };
+
+    std::fs::write(&dest, contents).with_context(|| format!("could not write {}", dest.display()))?;
+    Ok(dest)
+}
+
+/// The main checkout that `dir` belongs to: the parent of the shared `.git`.
+/// Falls back to `dir` itself outside a repository or for bare repos.
+fn main_checkout(dir: &Path) -> PathBuf {
+    git::common_dir(dir)
+        .ok()
+        .filter(|common| common.file_name().is_some_and(|name| name == ".git"))
+        .and_then(|common| common.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| dir.to_path_buf())
+}
+
+fn global_config_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("workz").join("config.toml"))
+}
+
+/// Parse a config file. A file that exists but can't be read or parsed is
+/// skipped with a warning rather than silently.
+fn load_file(path: &Path) -> Option<Config> {
     if !path.exists() {
         return None;
     }
-    let contents = std::fs::read_to_string(&path).ok()?;
-    toml::from_str(&contents).ok()
+    let parsed = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()));
+    match parsed {
+        Ok(config) => Some(config),
+        Err(e) => {
+            eprintln!("  warning: ignoring {}: {}", path.display(), e.trim_end());
+            None
+        }
+    }
+}
+
+fn load_global_config() -> Option<Config> {
+    load_file(&global_config_path()?)
+}
+
+fn load_project_config(repo_root: &Path) -> Option<Config> {
+    load_file(&repo_root.join(CONFIG_FILE))
+}
+
+fn load_local_config(worktree: &Path) -> Option<Config> {
+    load_file(&worktree.join(LOCAL_CONFIG_FILE))
+}
+
+/// One config file that exists and parsed, with where it came from.
+pub struct Layer {
+    pub path: PathBuf,
+    pub config: Config,
+}
+
+/// The layers `load_worktree_config` merges, lowest precedence first
+/// (global, project, worktree-local), for showing where settings come from.
+pub fn layers(repo_root: &Path, worktree: &Path) -> Vec<Layer> {
+    let paths = [
+        global_config_path(),
+        Some(main_checkout(repo_root).join(CONFIG_FILE)),
+        Some(worktree.join(LOCAL_CONFIG_FILE)),
+    ];
+    paths
+        .into_iter()
+        .flatten()
+        .filter_map(|path| Some(Layer { config: load_file(&path)?, path }))
+        .collect()
 }
 
 /// Merge two configs. Project values override global values.
@@ -182,7 +695,16 @@ fn merge_configs(global: Config, project: Config) -> Config {
     // If project specifies sync values, use them; otherwise fall back to global
     let is_project_sync_default = project.sync.symlink == default_sync.symlink
         && project.sync.copy == default_sync.copy
-        && project.sync.ignore.is_empty();
+        && project.sync.ignore.is_empty()
+        && project.sync.auto_install
+        && !project.sync.install_fallback
+        && project.sync.install_cmd.is_none()
+        && !project.sync.install_tool_versions
+        && !project.sync.copy_gitignored_only
+        && project.sync.install_jobs.is_none()
+        && !project.sync.resolve_source_symlinks
+        && !project.sync.install_in_source
+        && !project.sync.quiet_install;
 
     let sync = if is_project_sync_default {
         // Project didn't customize sync, use global
@@ -192,8 +714,12 @@ fn merge_configs(global: Config, project: Config) -> Config {
     };
 
     let hooks = HooksConfig {
-        post_start: project.hooks.post_start.or(global.hooks.post_start),
-        pre_done: project.hooks.pre_done.or(global.hooks.pre_done),
+        pre_start: merge_hook(global.hooks.pre_start, project.hooks.pre_start),
+        post_sync: merge_hook(global.hooks.post_sync, project.hooks.post_sync),
+        post_start: merge_hook(global.hooks.post_start, project.hooks.post_start),
+        pre_done: merge_hook(global.hooks.pre_done, project.hooks.pre_done),
+        post_done: merge_hook(global.hooks.post_done, project.hooks.post_done),
+        post_switch: merge_hook(global.hooks.post_switch, project.hooks.post_switch),
     };
 
     let default_iso = IsolationConfig::default();
@@ -205,5 +731,232 @@ fn merge_configs(global: Config, project: Config) -> Config {
         global.isolation
     };
 
-    Config { sync, hooks, isolation }
+    let list = if project.list.ignore_marker != default_ignore_marker() {
+        project.list
+    } else {
+        global.list
+    };
+
+    let worktree = WorktreeConfig {
+        init_submodules: project.worktree.init_submodules.or(global.worktree.init_submodules),
+        base_remote: project.worktree.base_remote.or(global.worktree.base_remote),
+        open_in_tmux: project.worktree.open_in_tmux.or(global.worktree.open_in_tmux),
+        branch_template: project.worktree.branch_template.or(global.worktree.branch_template),
+        signing_key: project.worktree.signing_key.or(global.worktree.signing_key),
+        commit_template: project.worktree.commit_template.or(global.worktree.commit_template),
+    };
+
+    let bootstrap = BootstrapConfig {
+        node: merge_hook(global.bootstrap.node, project.bootstrap.node),
+        python: merge_hook(global.bootstrap.python, project.bootstrap.python),
+        rust: merge_hook(global.bootstrap.rust, project.bootstrap.rust),
+        go: merge_hook(global.bootstrap.go, project.bootstrap.go),
+        java: merge_hook(global.bootstrap.java, project.bootstrap.java),
+    };
+
+    let done = DoneConfig {
+        ticket_pattern: project.done.ticket_pattern.or(global.done.ticket_pattern),
+        backup_untracked: project.done.backup_untracked.or(global.done.backup_untracked),
+    };
+
+    let switch = global.switch.overridden_by(project.switch);
+
+    let remotes = RemotesConfig {
+        push: project.remotes.push.or(global.remotes.push),
+        pull: project.remotes.pull.or(global.remotes.pull),
+    };
+
+    // Inner layers' rules are tried first
+    let git_config = project.git_config.into_iter().chain(global.git_config).collect();
+
+    Config { sync, hooks, isolation, list, worktree, bootstrap, done, switch, remotes, git_config }
+}
+
+/// Merge a worktree-local file over the global + project config. Like
+/// `merge_configs`, except a local hook replaces the inherited one rather than
+/// running after it: the local file exists to override.
+fn merge_local(config: Config, mut local: Config) -> Config {
+    let hooks = std::mem::take(&mut local.hooks);
+    let mut merged = merge_configs(config, local);
+    merged.hooks = HooksConfig {
+        pre_start: hooks.pre_start.or(merged.hooks.pre_start),
+        post_sync: hooks.post_sync.or(merged.hooks.post_sync),
+        post_start: hooks.post_start.or(merged.hooks.post_start),
+        pre_done: hooks.pre_done.or(merged.hooks.pre_done),
+        post_done: hooks.post_done.or(merged.hooks.post_done),
+        post_switch: hooks.post_switch.or(merged.hooks.post_switch),
+    };
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_accept_string_or_list() {
+        let config: Config = toml::from_str(
+            "[hooks]\npost_start = \"make setup\"\npre_done = [\"make stop\", \"make clean\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.hooks.post_start.unwrap().commands(), ["make setup"]);
+        assert_eq!(config.hooks.pre_done.unwrap().commands(), ["make stop", "make clean"]);
+    }
+
+    #[test]
+    fn hooks_from_both_layers_run_global_first() {
+        let global: Config = toml::from_str("[hooks]\npost_start = \"org-setup\"\n").unwrap();
+        let project: Config =
+            toml::from_str("[hooks]\npost_start = [\"npm ci\", \"npm run db\"]\npre_done = \"stop\"\n").unwrap();
+
+        let merged = merge_configs(global, project);
+        assert_eq!(merged.hooks.post_start.unwrap().commands(), ["org-setup", "npm ci", "npm run db"]);
+        assert_eq!(merged.hooks.pre_done.unwrap().commands(), ["stop"]);
+    }
+
+    #[test]
+    fn worktree_reads_main_checkout_config() {
+        let base = std::env::temp_dir().join(format!("workz-test-main-config-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let main = base.join("repo");
+        let wt = base.join("repo--feat");
+        std::fs::create_dir_all(&main).unwrap();
+        let git = |args: &[&str]| {
+            let ok = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&main)
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&["worktree", "add", "-q", "-b", "feat", wt.to_str().unwrap()]);
+
+        // Written after the branch was cut, and never committed.
+        std::fs::write(main.join(CONFIG_FILE), "[sync]\nsymlink = [\"only-in-main\"]\n").unwrap();
+
+        let config = load_worktree_config(&wt, &wt).unwrap();
+        assert_eq!(config.sync.symlink, ["only-in-main"]);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn starter_config_parses_to_defaults() {
+        let config: Config = toml::from_str(STARTER_CONFIG).unwrap();
+        assert_eq!(config.sync.symlink, default_symlink_dirs());
+        assert!(config.hooks.post_start.is_none());
+    }
+
+    #[test]
+    fn ticket_from_branch_name() {
+        let done = |pattern: &str| DoneConfig { ticket_pattern: Some(pattern.to_string()), ..Default::default() };
+        assert_eq!(done(r"[A-Z]+-\d+").ticket("PROJ-123-fix"), Some("PROJ-123".into()));
+        assert_eq!(done(r"^feat/(\d+)").ticket("feat/42-login"), Some("42".into()));
+        assert_eq!(done(r"[A-Z]+-\d+").ticket("cleanup"), None);
+        assert_eq!(DoneConfig::default().ticket("PROJ-1"), None);
+    }
+
+    #[test]
+    fn install_jobs_must_be_positive() {
+        let config: Config = toml::from_str("[sync]\ninstall_jobs = 2\n").unwrap();
+        assert_eq!(config.sync.install_jobs.map(|n| n.get()), Some(2));
+        assert!(toml::from_str::<Config>("[sync]\ninstall_jobs = 0\n").is_err());
+    }
+
+    #[test]
+    fn unknown_match_mode_keeps_the_rest_of_the_file() {
+        let config: Config =
+            toml::from_str("[switch]\nmatch = \"glob\"\n\n[hooks]\npost_start = \"make setup\"\n").unwrap();
+        assert_eq!(config.switch.match_mode, None);
+        assert_eq!(config.hooks.post_start.unwrap().commands(), ["make setup"]);
+    }
+
+    #[test]
+    fn switch_config_layers_and_validates_height() {
+        let global: Config = toml::from_str("[switch]\nheight = \"60%\"\nmatch = \"exact\"\n").unwrap();
+        let project: Config = toml::from_str("[switch]\nmatch = \"regex\"\n").unwrap();
+        let switch = merge_configs(global, project).switch;
+        assert_eq!(switch.height().unwrap(), "60%");
+        assert_eq!(switch.match_mode, Some(MatchMode::Regex));
+
+        let height = |h: &str| SwitchConfig { height: Some(h.into()), ..Default::default() };
+        assert_eq!(SwitchConfig::default().height().unwrap(), "40%");
+        assert_eq!(height("20").height().unwrap(), "20");
+        for bad in ["0", "150%", "tall", "40 %"] {
+            assert!(height(bad).height().is_err(), "{bad}");
+        }
+        assert_eq!(toml::from_str::<Config>("[switch]\nmatch = \"fuzzy-ish\"\n").unwrap().switch.match_mode, None);
+    }
+
+    #[test]
+    fn branch_template_expands_user_and_input() {
+        let worktree = |t: &str| WorktreeConfig { branch_template: Some(t.into()), ..Default::default() };
+        assert_eq!(worktree("{user}/{input}").expand_branch("login-fix", "Ada Lovelace"), "ada-lovelace/login-fix");
+        assert_eq!(worktree("feat/{input}").expand_branch("PROJ-1", "ada"), "feat/PROJ-1");
+        assert_eq!(WorktreeConfig::default().expand_branch("login-fix", "ada"), "login-fix");
+    }
+
+    #[test]
+    fn local_hooks_replace_inherited_ones() {
+        let global: Config = toml::from_str("[hooks]\npost_start = \"global-setup\"\n").unwrap();
+        let project: Config =
+            toml::from_str("[hooks]\npost_start = \"make setup\"\npre_done = \"make stop\"\n").unwrap();
+        let local: Config = toml::from_str("[hooks]\npost_start = \"make setup-lite\"\n").unwrap();
+
+        let config = merge_local(merge_configs(global, project), local);
+        assert_eq!(config.hooks.post_start.unwrap().commands(), ["make setup-lite"]);
+        assert_eq!(config.hooks.pre_done.unwrap().commands(), ["make stop"]);
+    }
+
+    #[test]
+    fn inner_layers_can_turn_worktree_flags_off() {
+        let global: Config = toml::from_str("[worktree]\ninit_submodules = true\nbase_remote = true\nopen_in_tmux = true\n").unwrap();
+        let project: Config = toml::from_str("[worktree]\ninit_submodules = false\nbase_remote = false\nopen_in_tmux = false\n").unwrap();
+        let worktree = merge_configs(global, project).worktree;
+        assert_eq!(worktree.init_submodules, Some(false));
+        assert_eq!(worktree.base_remote, Some(false));
+        assert_eq!(worktree.open_in_tmux, Some(false));
+    }
+
+    #[test]
+    fn project_can_turn_off_global_backup_untracked() {
+        let global: Config = toml::from_str("[done]\nbackup_untracked = true\n").unwrap();
+        let project: Config = toml::from_str("[done]\nbackup_untracked = false\n").unwrap();
+        assert_eq!(merge_configs(global, project).done.backup_untracked, Some(false));
+
+        let global: Config = toml::from_str("[done]\nbackup_untracked = true\n").unwrap();
+        assert_eq!(merge_configs(global, Config::default()).done.backup_untracked, Some(true));
+    }
+
+    #[test]
+    fn git_config_first_matching_rule_wins() {
+        let global: Config =
+            toml::from_str("[[git_config]]\npattern = \"*\"\nset = { \"user.email\" = \"me@home.dev\" }\n").unwrap();
+        let project: Config = toml::from_str(
+            "[[git_config]]\npattern = \"work/*\"\nset = { \"user.email\" = \"me@corp.com\" }\n",
+        )
+        .unwrap();
+        let config = merge_configs(global, project);
+        assert_eq!(config.git_config_for("work/api/v2").unwrap().set["user.email"], "me@corp.com");
+        assert_eq!(config.git_config_for("oss/fix").unwrap().set["user.email"], "me@home.dev");
+        assert!(Config::default().git_config_for("main").is_none());
+    }
+
+    #[test]
+    fn commit_template_must_exist() {
+        let root = std::env::temp_dir().join(format!("workz-test-template-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".github")).unwrap();
+        std::fs::write(root.join(".github/commit.txt"), "ticket: \n").unwrap();
+        let worktree = |t: &str| WorktreeConfig { commit_template: Some(t.into()), ..Default::default() };
+
+        let found = worktree(".github/commit.txt").commit_template_path(&root).unwrap();
+        assert_eq!(found, Some(root.join(".github/commit.txt")));
+        assert!(worktree("missing.txt").commit_template_path(&root).is_err());
+        assert_eq!(WorktreeConfig::default().commit_template_path(&root).unwrap(), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
@@ -6,7 +6,7 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::cli::AiTool;
-use crate::{config, git, sync, which_exists};
+use crate::{config, git, sync, which_exists, Outcome};
 
 // ── state ───────────────────────────────────────────────────────────────
 
@@ -147,7 +147,7 @@ pub fn cmd_start(
     println!("\nsyncing dependencies...");
     for (i, (ft, wt_path)) in created.iter().enumerate() {
         print!("  [{}/{}] {}... ", i + 1, n, ft.branch);
-        let _framework = sync::sync_worktree(&root, wt_path, &config.sync)?;
+        let _framework = sync::sync_worktree(&root, wt_path, &config)?;
         println!("done");
     }
 
@@ -250,7 +250,7 @@ pub fn cmd_status() -> Result<()> {
 
 // ── fleet run ───────────────────────────────────────────────────────────
 
-pub fn cmd_run(cmd_parts: &[String]) -> Result<()> {
+pub fn cmd_run(cmd_parts: &[String], parallel: usize) -> Result<()> {
     let root = git::repo_root()?;
     let state = load_state(&root)?;
 
@@ -263,24 +263,17 @@ pub fn cmd_run(cmd_parts: &[String]) -> Result<()> {
     println!("running '{}' in {} worktree{}...", shell_cmd, state.tasks.len(),
         if state.tasks.len() == 1 { "" } else { "s" });
 
-    // Run in each worktree using threads, collect results
-    let tasks = state.tasks.clone();
-    let handles: Vec<_> = tasks
-        .into_iter()
-        .map(|ft| {
-            let cmd = shell_cmd.clone();
-            std::thread::spawn(move || {
-                let output = Command::new("sh")
-                    .args(["-c", &cmd])
-                    .current_dir(&ft.path)
-                    .output();
-                (ft.branch, output)
-            })
-        })
-        .collect();
-
-    for handle in handles {
-        let (branch, result) = handle.join().expect("thread panicked");
+    // Run in each worktree on a bounded pool, collect results
+    let results = crate::parallel::map(state.tasks.clone(), parallel, |ft| {
+        let output = Command::new("sh")
+            .args(["-c", &shell_cmd])
+            .current_dir(&ft.path)
+            .output();
+        (ft.branch, output)
+    });
+
+    let mut failed = Vec::new();
+    for (branch, result) in results {
         match result {
             Ok(out) => {
                 let stdout = String::from_utf8_lossy(&out.stdout);
@@ -297,11 +290,21 @@ pub fn cmd_run(cmd_parts: &[String]) -> Result<()> {
                         eprintln!("    {}", line);
                     }
                 }
+                if !out.status.success() {
+                    failed.push(branch);
+                }
+            }
+            Err(e) => {
+                println!("\n  [{branch}] error: {e}");
+                failed.push(branch);
             }
-            Err(e) => println!("\n  [{branch}] error: {e}"),
         }
     }
 
+    if !failed.is_empty() {
+        println!("\nfailed in: {}", failed.join(", "));
+    }
+
     Ok(())
 }
 
@@ -340,13 +343,13 @@ pub fn cmd_done(force: bool) -> Result<()> {
 
 // ── fleet merge ─────────────────────────────────────────────────────────
 
-pub fn cmd_merge(base: Option<&str>, squash: bool, all: bool) -> Result<()> {
+pub fn cmd_merge(base: Option<&str>, squash: bool, all: bool) -> Result<Outcome> {
     let root = git::repo_root()?;
     let state = load_state(&root)?;
 
     if state.tasks.is_empty() {
         println!("fleet is empty");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     let base_branch = base
@@ -376,7 +379,7 @@ pub fn cmd_merge(base: Option<&str>, squash: bool, all: bool) -> Result<()> {
 
     if candidates.is_empty() {
         println!("no fleet worktrees available");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     println!("merging into: {}", root_branch);
@@ -422,14 +425,14 @@ pub fn cmd_merge(base: Option<&str>, squash: bool, all: bool) -> Result<()> {
                 .collect(),
             _ => {
                 println!("cancelled");
-                return Ok(());
+                return Ok(Outcome::Cancelled);
             }
         }
     };
 
     if selected_branches.is_empty() {
         println!("nothing selected");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     let mode = if squash { "squash" } else { "no-ff" };
@@ -485,22 +488,23 @@ pub fn cmd_merge(base: Option<&str>, squash: bool, all: bool) -> Result<()> {
     println!();
     println!("{}/{} branch{} merged", merged, selected_branches.len(),
         if selected_branches.len() == 1 { "" } else { "es" });
-    Ok(())
+    Ok(Outcome::Success)
 }
 
 // ── fleet pr ────────────────────────────────────────────────────────────
 
-pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
+pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<Outcome> {
     if !which_exists("gh") {
         anyhow::bail!("'gh' (GitHub CLI) not found — install it with: brew install gh");
     }
 
     let root = git::repo_root()?;
+    let remotes = config::load_config(&root)?.remotes;
     let state = load_state(&root)?;
 
     if state.tasks.is_empty() {
         println!("fleet is empty");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     let base_branch = base
@@ -515,7 +519,7 @@ pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
 
     if candidates.is_empty() {
         println!("no fleet worktrees available");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     let selected: Vec<&FleetTask> = if all {
@@ -553,7 +557,7 @@ pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
                 .collect(),
             _ => {
                 println!("cancelled");
-                return Ok(());
+                return Ok(Outcome::Cancelled);
             }
         };
 
@@ -565,7 +569,7 @@ pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
 
     if selected.is_empty() {
         println!("nothing selected");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
     let draft_str = if draft { " (draft)" } else { "" };
@@ -581,7 +585,7 @@ pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
     for ft in &selected {
         print!("  {} ... pushing... ", ft.branch);
 
-        if let Err(e) = git::push_branch(&root, &ft.branch) {
+        if let Err(e) = git::push_branch(&root, remotes.push(), &ft.branch) {
             println!("push failed: {}", e.to_string().lines().next().unwrap_or(""));
             continue;
         }
@@ -622,5 +626,5 @@ pub fn cmd_pr(base: Option<&str>, draft: bool, all: bool) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(Outcome::Success)
 }
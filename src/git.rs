@@ -1,19 +1,95 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::json;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
-/// Run a git command and return stdout as a trimmed string.
-fn git(args: &[&str]) -> Result<String> {
+use crate::logging;
+
+/// Run a git command and return its raw output, whatever the exit status.
+fn git_run(args: &[&str]) -> Result<Output> {
+    let started = logging::enabled().then(Instant::now);
     let output = Command::new("git")
         .args(args)
         .output()
         .context("failed to execute git — is it installed?")?;
 
+    if let Some(started) = started {
+        let success = output.status.success();
+        logging::record(json!({
+            "command": "git",
+            "args": args,
+            "success": success,
+            "exit_code": output.status.code(),
+            "stderr": if success { None } else { Some(String::from_utf8_lossy(&output.stderr).trim().to_string()) },
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+        }));
+    }
+
+    Ok(output)
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Show git's raw stderr alongside the friendly rewrite of a failure.
+pub fn set_verbose(on: bool) {
+    VERBOSE.store(on, Ordering::Relaxed);
+}
+
+/// Run a git command and return its raw output, bailing on a non-zero exit.
+fn git_output(args: &[&str]) -> Result<Output> {
+    let output = git_run(args)?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        let raw = format!("git {} failed: {}", args.join(" "), stderr.trim());
+        match friendly_error(&stderr) {
+            Some(msg) if VERBOSE.load(Ordering::Relaxed) => bail!("{msg}\n\n{raw}"),
+            Some(msg) => bail!("{msg} (rerun with --verbose for git's output)"),
+            None => bail!(raw),
+        }
     }
+    Ok(output)
+}
+
+/// Translate git stderr we see often into something actionable.
+/// Unknown failures return None and are reported verbatim.
+fn friendly_error(stderr: &str) -> Option<String> {
+    let quoted = |s: &str| s.split('\'').nth(1).map(str::to_string);
+    let line = stderr.lines().find(|l| l.starts_with("fatal:") || l.starts_with("error:"))?;
+
+    if line.contains("not a git repository") {
+        Some("not inside a git repository — cd into one (or `git init`) first".into())
+    } else if line.contains("a branch named") && line.contains("already exists") {
+        let branch = quoted(line)?;
+        Some(format!("branch '{branch}' already exists — run `workz start {branch}` without --base to check it out"))
+    } else if line.contains("already exists") {
+        let path = quoted(line)?;
+        Some(format!("a worktree already occupies {path} — run `workz switch` to jump to it, or `workz done` to remove it"))
+    } else if line.contains("is already checked out at") || line.contains("is already used by worktree at") {
+        let branch = quoted(line)?;
+        let path = line.rsplit('\'').nth(1)?;
+        Some(format!("branch '{branch}' is already checked out in {path} — run `workz switch {branch}` to go there"))
+    } else if line.contains("invalid reference") || line.contains("not a valid object name") {
+        let name = line.rsplit(": ").next()?.trim_matches('\'');
+        Some(format!("'{name}' is not a known branch, tag, or commit — run `workz fetch` if it only exists on the remote"))
+    } else if line.contains("a locked working tree") {
+        Some("the worktree is locked — run `workz unlock <branch>` first".into())
+    } else if line.contains("contains modified or untracked files") {
+        Some("the worktree has uncommitted changes — commit or stash them, or pass --force".into())
+    } else if let Some(pr) = line.split("couldn't find remote ref pull/").nth(1) {
+        let number = pr.split('/').next()?;
+        Some(format!("pull request #{number} not found on the remote — check the number (and [remotes] pull for forks)"))
+    } else if stderr.contains("Could not read from remote repository") {
+        Some("could not reach the remote — check your network and credentials".into())
+    } else {
+        None
+    }
+}
 
+/// Run a git command and return stdout as a trimmed string.
+fn git(args: &[&str]) -> Result<String> {
+    let output = git_output(args)?;
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
@@ -24,6 +100,13 @@ fn git_in(dir: &Path, args: &[&str]) -> Result<String> {
     git(&full_args)
 }
 
+/// Absolute path of the shared `.git` directory, as seen from `dir`.
+/// Worktrees all resolve to the main repo's `.git`.
+pub fn common_dir(dir: &Path) -> Result<PathBuf> {
+    let out = git_in(dir, &["rev-parse", "--path-format=absolute", "--git-common-dir"])?;
+    Ok(PathBuf::from(out))
+}
+
 /// Find the root of the main git repository (not a worktree).
 /// Uses --git-common-dir to always resolve to the main repo, even when
 /// called from inside a worktree.
@@ -52,6 +135,18 @@ pub fn repo_root() -> Result<PathBuf> {
     }
 }
 
+/// Top-level directory of the worktree containing the current directory.
+pub fn toplevel() -> Result<PathBuf> {
+    let toplevel = git(&["rev-parse", "--show-toplevel"])
+        .context("not inside a git repository")?;
+    Ok(PathBuf::from(toplevel))
+}
+
+/// The worktree the current directory is in (canonicalized), if any.
+pub fn current_worktree() -> Option<PathBuf> {
+    toplevel().ok()?.canonicalize().ok()
+}
+
 /// Get the repository name from the root path.
 pub fn repo_name(root: &Path) -> String {
     root.file_name()
@@ -61,10 +156,36 @@ pub fn repo_name(root: &Path) -> String {
 }
 
 /// Compute the worktree directory path: `../<repo>--<safe-branch>`.
+/// A repo at the filesystem root has no parent to put siblings in (and no
+/// name), so its worktrees go under `<data dir>/workz/worktrees/root--<branch>`.
 pub fn worktree_path(root: &Path, branch: &str) -> PathBuf {
-    let safe = branch.replace(['/', '\\'], "-");
-    let base = root.parent().unwrap_or(root);
-    base.join(format!("{}--{}", repo_name(root), safe))
+    let safe = safe_dir_name(branch);
+    let name = repo_name(root);
+    match root.parent() {
+        Some(parent) if !name.is_empty() => parent.join(format!("{}--{}", name, safe)),
+        _ => dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("workz")
+            .join("worktrees")
+            .join(format!("root--{}", safe)),
+    }
+}
+
+/// Turn a branch name into a portable directory name: path separators,
+/// whitespace and the characters Windows reserves (`: * ? " < > |`) become
+/// `-`. Leading dots are dropped so the directory isn't hidden, and trailing
+/// dots and spaces are dropped because Windows strips them.
+pub fn safe_dir_name(branch: &str) -> String {
+    let safe: String = branch
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_whitespace() || c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    safe.trim_start_matches('.').to_string()
 }
 
 /// Check whether a local branch exists.
@@ -73,15 +194,53 @@ pub fn branch_exists(name: &str) -> Result<bool> {
     Ok(result.is_ok())
 }
 
+/// Set `key` in the worktree's own config (`config.worktree`), leaving the
+/// other worktrees alone. Turns on `extensions.worktreeConfig`, which git
+/// needs before it reads per-worktree config at all.
+pub fn set_worktree_config(path: &Path, key: &str, value: &str) -> Result<()> {
+    git_in(path, &["config", "extensions.worktreeConfig", "true"])?;
+    git_in(path, &["config", "--worktree", key, value])?;
+    Ok(())
+}
+
+/// A git config value (any scope), if set.
+pub fn config_value(key: &str) -> Option<String> {
+    git(&["config", "--get", key]).ok().filter(|v| !v.is_empty())
+}
+
+/// Whether `remote`'s copy of `branch` is known locally (as of the last fetch).
+pub fn remote_branch_exists(remote: &str, branch: &str) -> bool {
+    git(&["rev-parse", "--verify", "--quiet", &format!("refs/remotes/{remote}/{branch}")]).is_ok()
+}
+
+/// Check if a tag exists.
+pub fn tag_exists(name: &str) -> bool {
+    git(&["rev-parse", "--verify", "--quiet", &format!("refs/tags/{name}")]).is_ok()
+}
+
 /// Create a new worktree. Creates the branch if it doesn't exist.
 pub fn worktree_add(path: &Path, branch: &str, base: Option<&str>) -> Result<()> {
+    add_worktree(path, branch, base, false)
+}
+
+/// Like `worktree_add`, but a newly created branch gets no upstream even when
+/// `base` is a remote-tracking ref (so it won't track e.g. `origin/main`).
+pub fn worktree_add_untracked(path: &Path, branch: &str, base: Option<&str>) -> Result<()> {
+    add_worktree(path, branch, base, true)
+}
+
+fn add_worktree(path: &Path, branch: &str, base: Option<&str>, no_track: bool) -> Result<()> {
     let path_str = path.to_str().unwrap_or(".");
 
     if branch_exists(branch)? {
         git(&["worktree", "add", path_str, branch])?;
     } else {
         // Create a new branch from base (or HEAD)
-        let mut args = vec!["worktree", "add", "-b", branch, path_str];
+        let mut args = vec!["worktree", "add"];
+        if no_track {
+            args.push("--no-track");
+        }
+        args.extend(["-b", branch, path_str]);
         if let Some(b) = base {
             args.push(b);
         }
@@ -91,6 +250,12 @@ pub fn worktree_add(path: &Path, branch: &str, base: Option<&str>) -> Result<()>
     Ok(())
 }
 
+/// Initialize and check out all submodules (recursively) in a worktree.
+pub fn submodule_update(path: &Path) -> Result<()> {
+    git_in(path, &["submodule", "update", "--init", "--recursive"])?;
+    Ok(())
+}
+
 /// Remove a worktree.
 pub fn worktree_remove(path: &Path, force: bool) -> Result<()> {
     let path_str = path.to_str().unwrap_or(".");
@@ -102,36 +267,226 @@ pub fn worktree_remove(path: &Path, force: bool) -> Result<()> {
     Ok(())
 }
 
+/// Keep git from pruning (or moving/removing) the worktree while its directory
+/// is unavailable, optionally recording why.
+pub fn worktree_lock(path: &Path, reason: Option<&str>) -> Result<()> {
+    let path_str = path.to_str().unwrap_or(".");
+    match reason {
+        Some(reason) => git(&["worktree", "lock", "--reason", reason, path_str])?,
+        None => git(&["worktree", "lock", path_str])?,
+    };
+    Ok(())
+}
+
+/// Undo `worktree_lock`.
+pub fn worktree_unlock(path: &Path) -> Result<()> {
+    git(&["worktree", "unlock", path.to_str().unwrap_or(".")])?;
+    Ok(())
+}
+
+/// Relocate a worktree directory, keeping git's bookkeeping in step.
+pub fn worktree_move(from: &Path, to: &Path) -> Result<()> {
+    git(&["worktree", "move", from.to_str().unwrap_or("."), to.to_str().unwrap_or(".")])?;
+    Ok(())
+}
+
 /// Delete a local branch.
 pub fn branch_delete(name: &str, force: bool) -> Result<()> {
+    ensure_deletable(name)?;
+    if let Some(wt) = worktree_list()?.iter().find(|wt| wt.branch == name) {
+        bail!(
+            "branch '{}' is still checked out in {} — remove that worktree first",
+            name,
+            wt.path.display()
+        );
+    }
+
     let flag = if force { "-D" } else { "-d" };
-    git(&["branch", flag, name])?;
+    git(&["branch", flag, name]).map_err(|e| {
+        let msg = e.to_string();
+        if msg.contains("not fully merged") {
+            anyhow!("branch '{name}' is not fully merged — use --force to delete it anyway")
+        } else if msg.contains("not found") {
+            anyhow!("branch '{name}' does not exist")
+        } else if msg.contains("checked out at") || msg.contains("used by worktree") {
+            anyhow!("branch '{name}' is still checked out in another worktree")
+        } else {
+            e
+        }
+    })?;
     Ok(())
 }
 
+/// Rename local branch `old` to `new` (`git branch -m`). A worktree with
+/// `old` checked out follows the rename, since git rewrites its HEAD too.
+#[allow(dead_code)] // for the upcoming `workz rename` command
+pub fn rename_branch(old: &str, new: &str) -> Result<()> {
+    rename_branch_in(Path::new("."), old, new)
+}
+
+fn rename_branch_in(dir: &Path, old: &str, new: &str) -> Result<()> {
+    let exists = |name: &str| git_in(dir, &["rev-parse", "--verify", &format!("refs/heads/{name}")]).is_ok();
+    if !exists(old) {
+        bail!("branch '{}' does not exist", old);
+    }
+    if exists(new) {
+        bail!("branch '{}' already exists — pick another name, or remove it first", new);
+    }
+    git_in(dir, &["branch", "-m", old, new])?;
+
+    // git rewrites every worktree's HEAD that points at `old`; make sure none
+    // was left behind on a branch that no longer exists.
+    let worktrees = parse_worktree_list(&git_in(dir, &["worktree", "list", "--porcelain"])?);
+    if let Some(wt) = worktrees.iter().find(|wt| wt.branch == old) {
+        bail!(
+            "renamed '{}' to '{}', but {} still points at '{}' — run `git -C {} checkout {}`",
+            old,
+            new,
+            wt.path.display(),
+            old,
+            wt.path.display(),
+            new
+        );
+    }
+    Ok(())
+}
+
+/// Refuse to delete trunk — the default branch, or whatever the main checkout
+/// has — no matter what flags were passed.
+pub fn ensure_deletable(name: &str) -> Result<()> {
+    let main_branch = repo_root().ok().and_then(|root| current_branch(&root).ok());
+    check_deletable(name, &default_branch(), main_branch.as_deref())
+}
+
+fn check_deletable(name: &str, default: &str, main_branch: Option<&str>) -> Result<()> {
+    if name == default {
+        bail!("refusing to delete '{name}': it is the default branch");
+    }
+    if main_branch == Some(name) {
+        bail!("refusing to delete '{name}': it is checked out in the main worktree");
+    }
+    Ok(())
+}
+
+/// The upstream `(remote, branch)` configured for a local branch, if any.
+/// Branches tracking another local branch (remote ".") have no upstream here.
+pub fn upstream(branch: &str) -> Option<(String, String)> {
+    let remote = git(&["config", "--get", &format!("branch.{branch}.remote")]).ok()?;
+    let merge = git(&["config", "--get", &format!("branch.{branch}.merge")]).ok()?;
+    if remote == "." {
+        return None;
+    }
+    let remote_branch = merge.strip_prefix("refs/heads/").unwrap_or(&merge).to_string();
+    Some((remote, remote_branch))
+}
+
+/// Delete a branch on a remote.
+pub fn push_delete(remote: &str, branch: &str) -> Result<()> {
+    git(&["push", remote, "--delete", branch])?;
+    Ok(())
+}
+
+/// The remote to base new work on: `origin` if present, otherwise the first one.
+pub fn primary_remote() -> Result<String> {
+    let remotes = git(&["remote"])?;
+    let mut names = remotes.lines().map(str::trim).filter(|r| !r.is_empty());
+    let first = names.next().map(str::to_string);
+    if remotes.lines().any(|r| r.trim() == "origin") {
+        return Ok("origin".to_string());
+    }
+    first.context("no git remote configured")
+}
+
+/// The default branch of `remote`: its recorded HEAD, else what the remote
+/// itself reports.
+pub fn remote_default_branch(remote: &str) -> Result<String> {
+    let prefix = format!("{remote}/");
+    if let Ok(head) = git(&["symbolic-ref", "--short", &format!("refs/remotes/{remote}/HEAD")]) {
+        if let Some(branch) = head.strip_prefix(&prefix) {
+            return Ok(branch.to_string());
+        }
+    }
+    // e.g. "ref: refs/heads/main\tHEAD"
+    let out = git(&["ls-remote", "--symref", remote, "HEAD"])?;
+    out.lines()
+        .find_map(|l| l.strip_prefix("ref: refs/heads/")?.split('\t').next())
+        .map(str::to_string)
+        .with_context(|| format!("could not determine the default branch of '{remote}'"))
+}
+
+/// Fetch a single branch from `remote`, updating its remote-tracking ref.
+pub fn fetch_branch(remote: &str, branch: &str) -> Result<()> {
+    git(&["fetch", remote, branch])?;
+    Ok(())
+}
+
+/// Fetch GitHub pull request `number` from `remote` into local `branch`.
+pub fn fetch_pr(remote: &str, number: u32, branch: &str) -> Result<()> {
+    git(&["fetch", remote, &format!("pull/{number}/head:{branch}")])?;
+    Ok(())
+}
+
+/// The remote the default branch tracks (`branch.<default>.remote`), else
+/// the primary remote.
+pub fn default_branch_remote() -> Result<String> {
+    match config_value(&format!("branch.{}.remote", default_branch())) {
+        Some(remote) if remote != "." => Ok(remote),
+        _ => primary_remote(),
+    }
+}
+
+/// Fetch from `remote` (or all remotes), optionally pruning deleted remote refs.
+/// Returns the ref-update lines git reports — empty when already up to date.
+pub fn fetch(remote: Option<&str>, prune: bool) -> Result<Vec<String>> {
+    let mut args = vec!["fetch"];
+    if prune {
+        args.push("--prune");
+    }
+    args.push(remote.unwrap_or("--all"));
+
+    // git reports ref updates on stderr
+    let output = git_output(&args)?;
+    Ok(String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|l| l.contains(" -> "))
+        .map(|l| l.trim().to_string())
+        .collect())
+}
+
 /// Prune stale worktree entries.
 pub fn worktree_prune() -> Result<String> {
     git(&["worktree", "prune", "-v"])
 }
 
 /// A parsed worktree entry.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Worktree {
     pub path: PathBuf,
     pub branch: String,
     pub is_bare: bool,
     pub is_detached: bool,
+    /// Why git would prune this entry (e.g. its directory is gone), if it would.
+    pub prunable: Option<String>,
+    /// Locked with `git worktree lock` (`workz lock`): kept even while its
+    /// directory is missing, e.g. on an unmounted drive.
+    pub is_locked: bool,
 }
 
 /// List all worktrees (parsed from porcelain output).
 pub fn worktree_list() -> Result<Vec<Worktree>> {
     let output = git(&["worktree", "list", "--porcelain"])?;
+    Ok(parse_worktree_list(&output))
+}
+
+fn parse_worktree_list(output: &str) -> Vec<Worktree> {
     let mut worktrees = Vec::new();
     let mut current_path: Option<PathBuf> = None;
     let mut current_branch = String::new();
     let mut is_bare = false;
     let mut is_detached = false;
+    let mut prunable = None;
+    let mut is_locked = false;
 
     for line in output.lines() {
         if let Some(path) = line.strip_prefix("worktree ") {
@@ -142,11 +497,14 @@ pub fn worktree_list() -> Result<Vec<Worktree>> {
                     branch: std::mem::take(&mut current_branch),
                     is_bare,
                     is_detached,
+                    prunable: prunable.take(),
+                    is_locked,
                 });
             }
             current_path = Some(PathBuf::from(path.trim()));
             is_bare = false;
             is_detached = false;
+            is_locked = false;
         } else if let Some(b) = line.strip_prefix("branch refs/heads/") {
             current_branch = b.trim().to_string();
         } else if line.trim() == "bare" {
@@ -154,6 +512,12 @@ pub fn worktree_list() -> Result<Vec<Worktree>> {
         } else if line.trim() == "detached" {
             is_detached = true;
             current_branch = "(detached)".to_string();
+        } else if line.trim() == "locked" || line.starts_with("locked ") {
+            is_locked = true;
+        } else if line.trim() == "prunable" {
+            prunable = Some(String::new());
+        } else if let Some(reason) = line.strip_prefix("prunable ") {
+            prunable = Some(reason.trim().to_string());
         }
     }
 
@@ -164,10 +528,22 @@ pub fn worktree_list() -> Result<Vec<Worktree>> {
             branch: current_branch,
             is_bare,
             is_detached,
+            prunable,
+            is_locked,
         });
     }
 
-    Ok(worktrees)
+    worktrees
+}
+
+/// Whether git ignores `path` (relative to `dir`). Not being ignored — or
+/// `dir` not being a repo — both read as false.
+pub fn is_ignored(dir: &Path, path: &Path) -> bool {
+    let dir = dir.to_str().unwrap_or(".");
+    let path = path.to_str().unwrap_or("");
+    git_run(&["-C", dir, "check-ignore", "-q", "--", path])
+        .map(|out| out.status.success())
+        .unwrap_or(false)
 }
 
 /// Check if a worktree has uncommitted changes.
@@ -176,6 +552,35 @@ pub fn is_dirty(path: &Path) -> Result<bool> {
     Ok(!status.is_empty())
 }
 
+/// The commit currently checked out in a worktree.
+pub fn head_commit(path: &Path) -> Result<String> {
+    git_in(path, &["rev-parse", "HEAD"])
+}
+
+/// The commit `rev` points at.
+pub fn rev_parse(rev: &str) -> Result<String> {
+    git(&["rev-parse", "--verify", &format!("{rev}^{{commit}}")])
+}
+
+/// Move the worktree's branch to `commit`, discarding its commits and any
+/// uncommitted changes.
+pub fn reset_hard(path: &Path, commit: &str) -> Result<()> {
+    git_in(path, &["reset", "--hard", commit])?;
+    Ok(())
+}
+
+/// A commit holding a worktree's current state, uncommitted changes to tracked
+/// files included. `git stash create` makes it without touching any ref; a clean
+/// worktree yields its HEAD.
+pub fn working_state(path: &Path) -> Result<String> {
+    let stash = git_in(path, &["stash", "create"])?;
+    if stash.is_empty() {
+        head_commit(path)
+    } else {
+        Ok(stash)
+    }
+}
+
 /// Get the current branch name in a directory.
 pub fn current_branch(path: &Path) -> Result<String> {
     git_in(path, &["branch", "--show-current"])
@@ -186,6 +591,26 @@ pub fn last_commit_relative(path: &Path) -> Option<String> {
     git_in(path, &["log", "-1", "--format=%cr"]).ok().filter(|s| !s.is_empty())
 }
 
+/// Unix timestamp of the last commit in a worktree.
+pub fn last_commit_timestamp(path: &Path) -> Option<i64> {
+    git_in(path, &["log", "-1", "--format=%ct"]).ok()?.parse().ok()
+}
+
+/// Commits the worktree's HEAD is ahead of / behind `upstream` (any revision;
+/// `@{upstream}` for the branch's tracking branch). None when it can't be
+/// resolved, e.g. the branch has no upstream.
+pub fn ahead_behind(path: &Path, upstream: &str) -> Option<(usize, usize)> {
+    let range = format!("{upstream}...HEAD");
+    let out = git_in(path, &["rev-list", "--left-right", "--count", &range]).ok()?;
+    parse_left_right(&out)
+}
+
+/// `rev-list --left-right --count <upstream>...HEAD` prints "<behind>\t<ahead>".
+fn parse_left_right(out: &str) -> Option<(usize, usize)> {
+    let (behind, ahead) = out.split_once(char::is_whitespace)?;
+    Some((ahead.trim().parse().ok()?, behind.trim().parse().ok()?))
+}
+
 /// Return the default base branch (main, then master, then HEAD).
 pub fn default_branch() -> String {
     for candidate in &["main", "master"] {
@@ -196,19 +621,35 @@ pub fn default_branch() -> String {
     "HEAD".to_string()
 }
 
+/// Whether every commit on `branch` is reachable from `base`.
+pub fn is_merged(branch: &str, base: &str) -> Result<bool> {
+    let output = git_run(&["merge-base", "--is-ancestor", branch, base])?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => bail!(
+            "could not compare '{}' with '{}': {}",
+            branch,
+            base,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
 /// Return a set of branch names that are fully merged into `base`.
 pub fn merged_branches(base: &str) -> Result<Vec<String>> {
     let output = git(&["branch", "--merged", base])?;
     Ok(output
         .lines()
-        .map(|l| l.trim().trim_start_matches("* ").to_string())
+        // `*` marks the current branch, `+` one checked out in another worktree
+        .map(|l| l.trim_start_matches(['*', '+', ' ']).trim().to_string())
         .filter(|b| !b.is_empty() && b != base)
         .collect())
 }
 
 /// List files with uncommitted changes (staged or unstaged) in a worktree.
 pub fn modified_files(path: &Path) -> Result<Vec<String>> {
-    let output = git_in(path, &["status", "--porcelain"])?;
+    let output = status_porcelain(path)?;
     Ok(output
         .lines()
         .filter(|l| l.len() > 3)
@@ -216,9 +657,50 @@ pub fn modified_files(path: &Path) -> Result<Vec<String>> {
         .collect())
 }
 
+/// List untracked (not ignored) files in a worktree, one entry per file.
+pub fn untracked_files(path: &Path) -> Result<Vec<String>> {
+    let output = git_in(path, &["status", "--porcelain", "--untracked-files=all"])?;
+    Ok(output
+        .lines()
+        .filter_map(|l| l.strip_prefix("?? "))
+        .map(|f| f.trim().trim_matches('"').to_string())
+        .collect())
+}
+
+/// Every git-ignored file under `path`, repo-relative (not collapsed to directories).
+pub fn ignored_files(path: &Path) -> Result<Vec<String>> {
+    let output = git_in(path, &["ls-files", "-z", "--others", "--ignored", "--exclude-standard"])?;
+    Ok(output.split('\0').filter(|f| !f.is_empty()).map(str::to_string).collect())
+}
+
+/// `git status --porcelain` output, untrimmed: the leading space of " M file"
+/// is part of the two-column status.
+fn status_porcelain(path: &Path) -> Result<String> {
+    let output = git_output(&["-C", path.to_str().unwrap_or("."), "status", "--porcelain"])?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Stash entries (`stash@{n}: message`) that were made with `branch` checked
+/// out. Stashes are repository-wide, so this is the best guess at which ones
+/// belong to a worktree.
+pub fn stashes_on(root: &Path, branch: &str) -> Result<Vec<String>> {
+    let output = git_in(root, &["stash", "list", "--format=%gd%x09%gs"])?;
+    Ok(filter_stashes(&output, branch))
+}
+
+fn filter_stashes(list: &str, branch: &str) -> Vec<String> {
+    let wip = format!("WIP on {branch}: ");
+    let named = format!("On {branch}: ");
+    list.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(_, subject)| subject.starts_with(&wip) || subject.starts_with(&named))
+        .map(|(name, subject)| format!("{name}: {subject}"))
+        .collect()
+}
+
 /// Like modified_files but preserves the status prefix (M, A, D, ??).
 pub fn modified_files_with_status(path: &Path) -> Result<Vec<(String, String)>> {
-    let output = git_in(path, &["status", "--porcelain"])?;
+    let output = status_porcelain(path)?;
     Ok(output
         .lines()
         .filter(|l| l.len() > 3)
@@ -251,8 +733,149 @@ pub fn commit_with_message(root: &Path, message: &str) -> Result<()> {
     Ok(())
 }
 
-/// Push a local branch to origin with upstream tracking (run from root).
-pub fn push_branch(root: &Path, branch: &str) -> Result<()> {
-    git_in(root, &["push", "-u", "origin", branch])?;
+/// Push a local branch to `remote` with upstream tracking (run from root).
+pub fn push_branch(root: &Path, remote: &str, branch: &str) -> Result<()> {
+    git_in(root, &["push", "-u", remote, branch])?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_dir_name_replaces_separators() {
+        assert_eq!(safe_dir_name("feat/login"), "feat-login");
+        assert_eq!(safe_dir_name("fix\\win"), "fix-win");
+        assert_eq!(safe_dir_name("plain"), "plain");
+    }
+
+    #[test]
+    fn safe_dir_name_replaces_windows_reserved_chars() {
+        assert_eq!(safe_dir_name("feature:thing"), "feature-thing");
+        assert_eq!(safe_dir_name(r#"a*b?c"d<e>f|g"#), "a-b-c-d-e-f-g");
+        assert_eq!(safe_dir_name("with space"), "with-space");
+    }
+
+    #[test]
+    fn safe_dir_name_trims_dots() {
+        assert_eq!(safe_dir_name(".hidden"), "hidden");
+        assert_eq!(safe_dir_name("release-1.0."), "release-1.0");
+        assert_eq!(safe_dir_name("wip. "), "wip");
+    }
+
+    #[test]
+    fn worktree_path_is_sibling_of_repo() {
+        let path = worktree_path(Path::new("/src/app"), "feature:thing");
+        assert_eq!(path, PathBuf::from("/src/app--feature-thing"));
+    }
+
+    #[test]
+    fn worktree_path_for_nested_repo_and_branch() {
+        let path = worktree_path(Path::new("/home/me/code/org/app"), "feat/auth/login");
+        assert_eq!(path, PathBuf::from("/home/me/code/org/app--feat-auth-login"));
+        let top = worktree_path(Path::new("/app"), "fix");
+        assert_eq!(top, PathBuf::from("/app--fix"));
+    }
+
+    #[test]
+    fn worktree_path_for_repo_at_filesystem_root() {
+        let path = worktree_path(Path::new("/"), "feat/x");
+        assert_ne!(path.parent(), Some(Path::new("/")));
+        assert!(path.ends_with("workz/worktrees/root--feat-x"));
+    }
+
+    #[test]
+    fn friendly_error_rewrites_common_failures() {
+        assert_eq!(
+            friendly_error("Preparing worktree\nfatal: '/tmp/repo--feat' already exists\n").unwrap(),
+            "a worktree already occupies /tmp/repo--feat — run `workz switch` to jump to it, or `workz done` to remove it"
+        );
+        assert_eq!(
+            friendly_error("fatal: 'feat' is already checked out at '/tmp/repo--feat'\n").unwrap(),
+            "branch 'feat' is already checked out in /tmp/repo--feat — run `workz switch feat` to go there"
+        );
+        assert_eq!(
+            friendly_error("fatal: invalid reference: origin/nope\n").unwrap(),
+            "'origin/nope' is not a known branch, tag, or commit — run `workz fetch` if it only exists on the remote"
+        );
+        assert_eq!(
+            friendly_error("fatal: couldn't find remote ref pull/999/head\n").unwrap(),
+            "pull request #999 not found on the remote — check the number (and [remotes] pull for forks)"
+        );
+        assert!(friendly_error("error: branch 'x' not found.\n").is_none());
+    }
+
+    #[test]
+    fn stashes_are_matched_by_branch() {
+        let list = "stash@{0}\tWIP on feat: 1a2b3c4 wip\nstash@{1}\tOn main: try\nstash@{2}\tOn feat: half-done\nstash@{3}\tOn feat-2: other\n";
+        assert_eq!(
+            filter_stashes(list, "feat"),
+            ["stash@{0}: WIP on feat: 1a2b3c4 wip", "stash@{2}: On feat: half-done"]
+        );
+    }
+
+    #[test]
+    fn worktree_list_parses_prunable() {
+        let porcelain = "worktree /repo\nHEAD abc\nbranch refs/heads/main\n\n\
+                         worktree /repo--gone\nHEAD def\nbranch refs/heads/gone\n\
+                         prunable gitdir file points to non-existent location\n";
+        let worktrees = parse_worktree_list(porcelain);
+        assert_eq!(worktrees.len(), 2);
+        assert_eq!(worktrees[0].prunable, None);
+        assert_eq!(worktrees[1].branch, "gone");
+        assert_eq!(
+            worktrees[1].prunable.as_deref(),
+            Some("gitdir file points to non-existent location")
+        );
+    }
+
+    #[test]
+    fn worktree_list_parses_locked() {
+        let porcelain = "worktree /repo\nHEAD abc\nbranch refs/heads/main\n\n\
+                         worktree /mnt/usb/repo--feat\nHEAD def\nbranch refs/heads/feat\nlocked on USB\n\n\
+                         worktree /repo--fix\nHEAD 123\nbranch refs/heads/fix\nlocked\n\n\
+                         worktree /repo--wip\nHEAD 456\nbranch refs/heads/wip\n";
+        let locked: Vec<_> = parse_worktree_list(porcelain).iter().map(|w| w.is_locked).collect();
+        assert_eq!(locked, [false, true, true, false]);
+    }
+
+    #[test]
+    fn rename_branch_moves_a_live_worktree_along() {
+        let base = std::env::temp_dir().join(format!("workz-test-rename-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let repo = base.join("repo");
+        let wt = base.join("repo--feat");
+        std::fs::create_dir_all(&repo).unwrap();
+        git_in(&repo, &["init", "-q", "-b", "main"]).unwrap();
+        git_in(&repo, &["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "init"]).unwrap();
+        git_in(&repo, &["branch", "other"]).unwrap();
+        git_in(&repo, &["worktree", "add", "-q", "-b", "feat", wt.to_str().unwrap()]).unwrap();
+
+        assert!(rename_branch_in(&repo, "missing", "x").is_err());
+        assert!(rename_branch_in(&repo, "feat", "other").is_err());
+
+        rename_branch_in(&repo, "feat", "feat-renamed").unwrap();
+        assert_eq!(current_branch(&wt).unwrap(), "feat-renamed");
+        let worktrees = parse_worktree_list(&git_in(&repo, &["worktree", "list", "--porcelain"]).unwrap());
+        assert!(worktrees.iter().any(|w| w.branch == "feat-renamed" && w.path.ends_with("repo--feat")));
+        assert!(worktrees.iter().all(|w| w.branch != "feat"));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn left_right_counts_are_behind_then_ahead() {
+        assert_eq!(parse_left_right("3\t2"), Some((2, 3)));
+        assert_eq!(parse_left_right("0\t0"), Some((0, 0)));
+        assert_eq!(parse_left_right(""), None);
+    }
+
+    #[test]
+    fn trunk_branches_are_never_deletable() {
+        assert!(check_deletable("main", "main", Some("main")).is_err());
+        assert!(check_deletable("main", "main", Some("develop")).is_err());
+        assert!(check_deletable("develop", "main", Some("develop")).is_err());
+        assert!(check_deletable("feat/x", "main", Some("main")).is_ok());
+    }
+}
@@ -0,0 +1,128 @@
+//! Visit history behind `workz recent` and the `switch` ordering: how often and
+//! how recently each worktree was cd'd into, scored like zoxide's frecency.
+//! Stored per user in `~/.local/state/workz/history.json`, shared by all repos.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Once the visit counts add up to this, every count is scaled down by 10%
+/// and entries that fall below one visit are forgotten (zoxide's aging).
+const MAX_TOTAL: f64 = 10_000.0;
+
+pub type History = BTreeMap<PathBuf, Visits>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Visits {
+    pub count: f64,
+    /// Unix seconds of the latest visit
+    pub last: u64,
+}
+
+impl Visits {
+    /// zoxide's frecency: the visit count, weighted by how recent the last one was.
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.count * weight
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("workz").join("history.json"))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn load() -> History {
+    let Some(path) = history_path() else {
+        return History::default();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return History::default();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save(history: &History) -> Result<()> {
+    let Some(path) = history_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(history)?)?;
+    Ok(())
+}
+
+/// Count a visit to the worktree at `path`. Worktrees that no longer exist
+/// are dropped along the way.
+pub fn record_access(path: &Path) -> Result<()> {
+    let mut history = load();
+    history.retain(|p, _| p.exists());
+    visit(&mut history, path, now());
+    save(&history)
+}
+
+fn visit(history: &mut History, path: &Path, now: u64) {
+    let entry = history.entry(path.to_path_buf()).or_insert(Visits { count: 0.0, last: now });
+    entry.count += 1.0;
+    entry.last = now;
+
+    if history.values().map(|v| v.count).sum::<f64>() > MAX_TOTAL {
+        for visits in history.values_mut() {
+            visits.count *= 0.9;
+        }
+        history.retain(|_, v| v.count >= 1.0);
+    }
+}
+
+/// Visited paths, highest frecency first.
+pub fn ranked_paths() -> Vec<PathBuf> {
+    rank(&load(), now())
+}
+
+fn rank(history: &History, now: u64) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = history.iter().map(|(path, v)| (path, v.score(now))).collect();
+    paths.sort_by(|a, b| b.1.total_cmp(&a.1));
+    paths.into_iter().map(|(path, _)| path.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_visits_outrank_old_frequent_ones() {
+        let now = 100 * WEEK;
+        let mut history = History::new();
+        for _ in 0..6 {
+            visit(&mut history, Path::new("/old"), now - 2 * WEEK);
+        }
+        for _ in 0..2 {
+            visit(&mut history, Path::new("/today"), now - 2 * HOUR);
+        }
+        visit(&mut history, Path::new("/now"), now);
+
+        // 6 × 0.25 = 1.5, 2 × 2 = 4, 1 × 4 = 4 (ties keep path order)
+        assert_eq!(rank(&history, now), [PathBuf::from("/now"), "/today".into(), "/old".into()]);
+        assert_eq!(history[Path::new("/old")].count, 6.0);
+    }
+}
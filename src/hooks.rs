@@ -0,0 +1,163 @@
+//! Running the `[hooks]` commands. The CLI streams their output to the
+//! terminal; with `--quiet` or `--log-file`, and always under the MCP server
+//! (whose stdout is the protocol), the combined output is captured instead.
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::{config, logging};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static SHOW_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// `--quiet` hides hook output unless the hook fails; `--show-hooks-output`
+/// shows it regardless.
+pub fn set_output(quiet: bool, show: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    SHOW_OUTPUT.store(show, Ordering::Relaxed);
+}
+
+fn show_output() -> bool {
+    !QUIET.load(Ordering::Relaxed) || SHOW_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// The variables every hook gets: WORKZ_BRANCH, WORKZ_WORKTREE_PATH and
+/// WORKZ_REPO_ROOT, paths canonicalized. Before `start` creates the worktree
+/// only its parent directory can be resolved.
+pub fn env(root: &Path, wt_path: &Path, branch: &str) -> Vec<(String, String)> {
+    let resolve = |path: &Path| {
+        path.canonicalize()
+            .or_else(|_| {
+                let parent = path.parent().unwrap_or(path).canonicalize()?;
+                Ok::<_, std::io::Error>(parent.join(path.file_name().unwrap_or_default()))
+            })
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .into_owned()
+    };
+    vec![
+        ("WORKZ_BRANCH".to_string(), branch.to_string()),
+        ("WORKZ_WORKTREE_PATH".to_string(), resolve(wt_path)),
+        ("WORKZ_REPO_ROOT".to_string(), resolve(root)),
+    ]
+}
+
+fn shell(cmd: &str, dir: &Path, env: &[(String, String)]) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", cmd]).envs(env.iter().cloned()).current_dir(dir);
+    command
+}
+
+/// Run each command of `hook` in `dir` with `env` added; a failing command
+/// is a warning, not an error.
+pub fn run(name: &str, hook: &Option<config::Hook>, dir: &Path, env: &[(String, String)]) -> Result<()> {
+    for cmd in hook.iter().flat_map(config::Hook::commands) {
+        run_command(name, cmd, dir, env)?;
+    }
+    Ok(())
+}
+
+/// Run one hook command for the CLI, streaming its output unless it has to be
+/// captured for `--quiet` or the log file. Failure is reported as a warning.
+pub fn run_command(name: &str, cmd: &str, dir: &Path, env: &[(String, String)]) -> Result<()> {
+    println!("  running {} hook...", name);
+    let status = if show_output() && !logging::enabled() {
+        shell(cmd, dir, env).status()?
+    } else {
+        let (status, output) = capture(name, cmd, dir, env)?;
+        if show_output() || !status.success() {
+            print!("{}", output);
+        }
+        status
+    };
+    if !status.success() {
+        eprintln!("  warning: {} hook exited with {}", name, status);
+    }
+    Ok(())
+}
+
+/// Run each command of `hook` with stdout and stderr collected, for the MCP
+/// server. Returns a transcript of what ran and what it printed.
+pub fn run_captured(name: &str, hook: &Option<config::Hook>, dir: &Path, env: &[(String, String)]) -> Result<String> {
+    let mut transcript = String::new();
+    for cmd in hook.iter().flat_map(config::Hook::commands) {
+        let (status, output) = capture(name, cmd, dir, env)?;
+        transcript.push_str(&format!("$ {}  ({} hook)\n{}", cmd, name, output));
+        if !status.success() {
+            transcript.push_str(&format!("warning: {} hook exited with {}\n", name, status));
+        }
+    }
+    Ok(transcript)
+}
+
+/// Run `cmd` with stdout and stderr interleaved into one file and stdin
+/// closed, recording the result in the log file when there is one.
+///
+/// A file rather than a pipe: something the hook backgrounds (`npm run dev &`)
+/// inherits the output and would hold a pipe open long after the hook exits.
+/// Only what was written by the time the hook exits is returned.
+fn capture(name: &str, cmd: &str, dir: &Path, env: &[(String, String)]) -> Result<(ExitStatus, String)> {
+    static SEQ: AtomicUsize = AtomicUsize::new(0);
+    let started = logging::enabled().then(Instant::now);
+    let out_path = std::env::temp_dir().join(format!(
+        "workz-hook-{}-{}.log",
+        std::process::id(),
+        SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    // create_new: never follow something already planted at that path
+    let out = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&out_path)
+        .with_context(|| format!("could not create {}", out_path.display()))?;
+    let status = shell(cmd, dir, env)
+        .stdin(Stdio::null())
+        .stdout(out.try_clone()?)
+        .stderr(out)
+        .status()
+        .with_context(|| format!("failed to run {} hook", name));
+    let bytes = std::fs::read(&out_path).unwrap_or_default();
+    let _ = std::fs::remove_file(&out_path);
+    let status = status?;
+    let output = String::from_utf8_lossy(&bytes).into_owned();
+
+    if let Some(started) = started {
+        logging::record(json!({
+            "command": "hook",
+            "hook": name,
+            "run": cmd,
+            "success": status.success(),
+            "exit_code": status.code(),
+            "output": output,
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+        }));
+    }
+
+    Ok((status, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_combines_stdout_and_stderr() {
+        let env = [("WORKZ_BRANCH".to_string(), "feat".to_string())];
+        let (status, output) =
+            capture("post_start", "echo out $WORKZ_BRANCH; echo err >&2; exit 3", &std::env::temp_dir(), &env).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(output, "out feat\nerr\n");
+    }
+
+    #[test]
+    fn capture_returns_when_the_hook_exits_despite_background_jobs() {
+        let started = Instant::now();
+        let (status, output) = capture("post_start", "echo up; sleep 20 &", &std::env::temp_dir(), &[]).unwrap();
+        assert!(status.success());
+        assert_eq!(output, "up\n");
+        assert!(started.elapsed().as_secs() < 10);
+    }
+}
@@ -173,6 +173,17 @@ pub fn release_isolation(branch: &str) -> Result<()> {
     Ok(())
 }
 
+/// Record that the branch's worktree now lives at `path`. Called by cmd_move.
+pub fn relocate_isolation(branch: &str, path: &Path) -> Result<()> {
+    let slug = branch_to_slug(branch);
+    let mut registry = load_registry();
+    if let Some(alloc) = registry.allocations.get_mut(&slug) {
+        alloc.worktree_path = path.to_string_lossy().to_string();
+        save_registry(&registry)?;
+    }
+    Ok(())
+}
+
 /// Best-effort: drop the PostgreSQL database for a branch.
 pub fn drop_database(branch: &str) {
     let slug = branch_to_slug(branch);
@@ -243,7 +254,7 @@ fn write_env_local(wt_path: &Path, alloc: &PortAllocation, framework: Framework)
 
 // ── Timestamp helpers ────────────────────────────────────────────────────────
 
-fn rfc3339_now() -> String {
+pub fn rfc3339_now() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let secs = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -0,0 +1,40 @@
+//! Optional structured trace written by `--log-file`: one JSON object per line.
+//! Nothing is measured or serialized unless a log file was opened.
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Open `path` for appending; subsequent records go there.
+pub fn init(path: &Path) -> Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("could not open log file {}", path.display()))?;
+    let _ = LOG_FILE.set(Mutex::new(file));
+    Ok(())
+}
+
+/// Whether a log file is active — callers skip timing work otherwise.
+pub fn enabled() -> bool {
+    LOG_FILE.get().is_some()
+}
+
+/// Append a record, stamped with the current time. Write errors are ignored
+/// so logging can never break the command being traced.
+pub fn record(mut entry: Value) {
+    let Some(file) = LOG_FILE.get() else {
+        return;
+    };
+    if let Value::Object(map) = &mut entry {
+        map.insert("ts".into(), Value::String(crate::isolation::rfc3339_now()));
+    }
+    if let Ok(mut f) = file.lock() {
+        let _ = writeln!(f, "{}", entry);
+    }
+}
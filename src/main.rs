@@ -2,150 +2,513 @@ mod cli;
 mod config;
 mod fleet;
 mod git;
+mod history;
+mod hooks;
 mod isolation;
+mod logging;
+mod matcher;
 mod mcp;
+mod names;
+mod parallel;
 mod serve;
 mod sync;
 mod tui;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
-use cli::{AiTool, Commands, FleetCmd, Shell};
+use cli::{AiTool, Commands, ConfigCmd, DoneArgs, FleetCmd, HooksCmd, Shell, StartArgs};
 use skim::prelude::*;
 use std::io::Cursor;
-use std::process::Command;
+use std::process::{Command, ExitCode};
 
 /// Sentinel prefix for shell integration — the wrapper function parses this to cd.
 const CD_PREFIX: &str = "__workz_cd:";
+/// Sentinel prefix for a command the wrapper evals in the user's shell after cd-ing.
+/// Always followed by the wrapper's per-invocation token (see `run_token`).
+const RUN_PREFIX: &str = "__workz_run:";
+
+/// Env var the shell wrapper uses to hand workz a fresh random token.
+const RUN_TOKEN_VAR: &str = "WORKZ_RUN_TOKEN";
+
+static RUN_TOKEN: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+
+/// Take the wrapper's token out of the environment, so hooks, git and
+/// installers we spawn can't see it and forge `__workz_run:` lines of their own.
+fn take_run_token() {
+    let token = std::env::var(RUN_TOKEN_VAR)
+        .ok()
+        .filter(|t| !t.is_empty() && t.chars().all(|c| c.is_ascii_alphanumeric()));
+    std::env::remove_var(RUN_TOKEN_VAR);
+    let _ = RUN_TOKEN.set(token);
+}
+
+/// The token to tag `__workz_run:` lines with; `None` when not running under
+/// the shell wrapper, in which case nothing is emitted for it to eval.
+fn run_token() -> Option<&'static str> {
+    RUN_TOKEN.get().and_then(|t| t.as_deref())
+}
+
+/// Exit code for failed commands.
+const EXIT_ERROR: u8 = 1;
+/// Exit code when the user aborts (picker dismissed, confirmation declined) —
+/// mirrors the shell convention for SIGINT so `workz switch || echo aborted` works.
+const EXIT_CANCELLED: u8 = 130;
+
+/// How a command finished. Errors travel separately through `Result`.
+pub enum Outcome {
+    Success,
+    Cancelled,
+}
 
-fn main() -> Result<()> {
+impl From<()> for Outcome {
+    fn from(_: ()) -> Self {
+        Outcome::Success
+    }
+}
+
+fn main() -> ExitCode {
     let cli = cli::Cli::parse();
 
+    match run(cli) {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::Cancelled) => ExitCode::from(EXIT_CANCELLED),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run(cli: cli::Cli) -> Result<Outcome> {
+    if let Some(path) = &cli.log_file {
+        logging::init(path)?;
+    }
+    git::set_verbose(cli.verbose);
+    hooks::set_output(cli.quiet, cli.show_hooks_output);
+    take_run_token();
+    let globals = cli.global_args();
+
     let Some(command) = cli.command else {
-        return tui::run_dashboard();
+        return tui::run_dashboard().map(Into::into);
     };
 
     match command {
-        Commands::Start {
-            branch,
-            base,
-            no_sync,
-            ai,
-            ai_tool,
-            docker,
-            isolated,
-        } => cmd_start(&branch, base.as_deref(), no_sync, ai, &ai_tool, docker, isolated),
-        Commands::List => cmd_list(),
-        Commands::Switch { query } => cmd_switch(query.as_deref()),
-        Commands::Done {
-            branch,
-            force,
-            delete_branch,
-            cleanup_db,
-        } => cmd_done(branch.as_deref(), force, delete_branch, cleanup_db),
-        Commands::Sync => cmd_sync(),
-        Commands::Status => cmd_status(),
-        Commands::Clean { merged, base } => cmd_clean(merged, base.as_deref()),
+        Commands::Start(args) => cmd_start(&args, true).map(Into::into),
+        Commands::New(args) => cmd_start(&args, false).map(Into::into),
+        Commands::List { all, bars, watch, by_project_type, no_size, sizes } => {
+            let style = ListStyle { bars, sizes: !no_size, recursive_sizes: sizes };
+            cmd_list(all, style, watch, by_project_type).map(Into::into)
+        }
+        Commands::Switch { query, create, all, height, preview, exact, regex, no_tui, multi, editor } => {
+            let match_mode = match (exact, regex) {
+                (true, _) => Some(config::MatchMode::Exact),
+                (_, true) => Some(config::MatchMode::Regex),
+                _ => None,
+            };
+            let picker = config::SwitchConfig { height, preview, reverse: None, match_mode };
+            if multi && !editor.is_gui() {
+                bail!("--multi opens worktrees side by side in an editor — use --editor cursor, code or windsurf");
+            }
+            cmd_switch(query.as_deref(), create, all, picker, no_tui, multi.then_some(editor))
+        }
+        Commands::Which { branch } => cmd_which(&branch).map(Into::into),
+        Commands::Recent => cmd_recent().map(Into::into),
+        Commands::Lock { branch, reason } => cmd_lock(&branch, reason.as_deref()).map(Into::into),
+        Commands::Unlock { branch } => cmd_unlock(&branch).map(Into::into),
+        Commands::Move { branch, new_path } => cmd_move(&branch, &new_path).map(Into::into),
+        Commands::Owners { files } => cmd_owners(&files).map(Into::into),
+        Commands::Diff { a, b, working } => cmd_diff(&a, b.as_deref(), working).map(Into::into),
+        Commands::Done(args) => cmd_done(&args, &globals),
+        Commands::Sync => cmd_sync().map(Into::into),
+        Commands::Adopt { path } => cmd_adopt(&path).map(Into::into),
+        Commands::Status => cmd_status().map(Into::into),
+        Commands::Stats => cmd_stats().map(Into::into),
+        Commands::Clean { merged, base, dry_run } => {
+            cmd_clean(merged, base.as_deref(), dry_run).map(Into::into)
+        }
+        Commands::Top { interval } => cmd_top(interval.max(1)).map(Into::into),
+        Commands::Fetch { remote, no_prune } => cmd_fetch(remote.as_deref(), !no_prune).map(Into::into),
         Commands::Fleet { cmd } => match cmd {
             FleetCmd::Start { mut tasks, from, agent, base } => {
                 if let Some(path) = from {
                     let mut file_tasks = fleet::parse_task_file(&path)?;
                     tasks.append(&mut file_tasks);
                 }
-                fleet::cmd_start(tasks, &agent, base.as_deref())
+                fleet::cmd_start(tasks, &agent, base.as_deref()).map(Into::into)
+            }
+            FleetCmd::Status => fleet::cmd_status().map(Into::into),
+            FleetCmd::Run { cmd, parallel } => {
+                fleet::cmd_run(&cmd, parallel.unwrap_or_else(parallel::default_jobs)).map(Into::into)
             }
-            FleetCmd::Status => fleet::cmd_status(),
-            FleetCmd::Run { cmd } => fleet::cmd_run(&cmd),
-            FleetCmd::Done { force } => fleet::cmd_done(force),
+            FleetCmd::Done { force } => fleet::cmd_done(force).map(Into::into),
             FleetCmd::Merge { base, squash, all } => fleet::cmd_merge(base.as_deref(), squash, all),
             FleetCmd::Pr { base, draft, all } => fleet::cmd_pr(base.as_deref(), draft, all),
         },
-        Commands::Serve { port, no_open } => serve::run(port, no_open),
-        Commands::Mcp => mcp::run(),
-        Commands::Init { shell } => cmd_init(&shell),
+        Commands::Serve { port, no_open } => serve::run(port, no_open).map(Into::into),
+        Commands::Mcp { max_message_bytes, idle_timeout } => {
+            mcp::run(max_message_bytes, idle_timeout.map(std::time::Duration::from_secs)).map(Into::into)
+        }
+        Commands::Config { cmd } => match cmd {
+            ConfigCmd::Init { mirror_config, force } => {
+                let root = git::repo_root()?;
+                let path = config::init_project_config(&root, mirror_config.as_deref(), force)?;
+                println!("wrote {}", path.display());
+                Ok(Outcome::Success)
+            }
+        },
+        Commands::Hooks { cmd: HooksCmd::List } => cmd_hooks_list().map(Into::into),
+        Commands::Version { check } => cmd_version(check).map(Into::into),
+        Commands::Init { shell, completions_only, wrapper_only } => {
+            cmd_init(&shell, completions_only, wrapper_only).map(Into::into)
+        }
     }
 }
 
 // ── start ──────────────────────────────────────────────────────────────
 
-fn cmd_start(
-    branch: &str,
-    base: Option<&str>,
-    no_sync: bool,
-    ai: bool,
-    ai_tool: &AiTool,
-    docker: bool,
-    isolated: bool,
-) -> Result<()> {
+/// `start` (`cd` true) ends by handing the path to the shell wrapper; `new`
+/// just prints it, for scripts that don't want the cd sentinel.
+fn cmd_start(args: &StartArgs, cd: bool) -> Result<()> {
     let root = git::repo_root()?;
-    let wt_path = git::worktree_path(&root, branch);
+    let branch = templated_branch(&root, args)?;
+    let branch = branch.as_str();
+    // --name decouples the directory from the branch: `../<repo>--<name>`
+    let dir_name = args.name.as_deref().unwrap_or(branch);
+    let wt_path = git::worktree_path(&root, dir_name);
 
-    if wt_path.exists() {
+    if wt_path.exists() && is_registered_worktree(&wt_path)? {
         println!("worktree already exists at {}", wt_path.display());
-        println!("{}{}", CD_PREFIX, wt_path.display());
+        if args.shell {
+            return spawn_shell(&wt_path);
+        }
+        finish_start(&wt_path, cd);
         return Ok(());
     }
+    if wt_path.exists() {
+        clear_stray_dir(&wt_path, args.reuse)?;
+    }
+
+    if git::current_branch(&root).is_ok_and(|main| main == branch) {
+        bail!(
+            "'{}' is checked out in the main worktree ({}) — branch off it instead: `workz start <new-branch> --base {}`",
+            branch,
+            root.display(),
+            branch
+        );
+    }
 
     println!("creating worktree for branch '{}'", branch);
+    if git::safe_dir_name(dir_name) != branch {
+        let dir = wt_path.file_name().unwrap_or_default().to_string_lossy();
+        println!("  branch '{}' → directory '{}'", branch, dir);
+    }
 
-    git::worktree_add(&wt_path, branch, base)?;
+    // Capture the source worktree before creating the new one
+    let untracked_source = if args.copy_untracked { Some(git::toplevel()?) } else { None };
+    let ignored_source = if args.clone_ignored { Some(git::toplevel()?) } else { None };
+
+    // --from pins the base to the other worktree's HEAD, which may be ahead of
+    // any ref another machine would see
+    let from_base = match &args.from {
+        Some(from) => {
+            let (from_path, _) = find_worktree(&root, from)?;
+            Some(git::head_commit(&from_path)?)
+        }
+        None => None,
+    };
+    let tag_base = match &args.from_tag {
+        Some(tag) if git::tag_exists(tag) => Some(format!("refs/tags/{}", tag)),
+        Some(tag) => bail!("tag '{}' not found — run `workz fetch` if it only exists on the remote", tag),
+        None => None,
+    };
+    let base = from_base.as_deref().or(tag_base.as_deref()).or(args.base.as_deref());
+
+    let root_config = config::load_config(&root)?;
+    // Checked before creating anything so a typo doesn't leave a half-set-up worktree
+    let commit_template = root_config.worktree.commit_template_path(&root)?;
+
+    let workz_env = hooks::env(&root, &wt_path, branch);
+    hooks::run("pre_start", &root_config.hooks.pre_start, &root, &workz_env)?;
+
+    let new_branch = !git::branch_exists(branch)?;
+    if let Some(pr) = args.from_pr {
+        let remote = match &root_config.remotes.pull {
+            Some(remote) => remote.clone(),
+            None => git::default_branch_remote()?,
+        };
+        println!("  fetching pull request #{} from {} into '{}'...", pr, remote, branch);
+        git::fetch_pr(&remote, pr, branch)?;
+    }
+
+    let reset_to = if args.reset_base && git::branch_exists(branch)? {
+        Some(reset_target(&root, branch, base.unwrap_or("HEAD"), args.force)?)
+    } else {
+        None
+    };
+
+    let use_remote_base = base.is_none()
+        && !git::branch_exists(branch)?
+        && (args.base_remote || root_config.worktree.base_remote.unwrap_or(false));
+
+    if use_remote_base {
+        // The new branch only starts at the remote tip; it shouldn't track it
+        let remote = root_config.remotes.pull().context("--base-remote needs a git remote")?;
+        git::worktree_add_untracked(&wt_path, branch, Some(&remote_base(&remote)?))?;
+    } else if args.no_track {
+        git::worktree_add_untracked(&wt_path, branch, base)?;
+    } else {
+        git::worktree_add(&wt_path, branch, base)?;
+    }
     println!("  worktree created at {}", wt_path.display());
 
-    let config = config::load_config(&root)?;
+    // Everything from here to docker is one setup step; a hard error in it
+    // leaves a half-initialized worktree, which --rollback-on-error removes
+    let setup = || -> Result<()> {
+        if let Some(commit) = &reset_to {
+            git::reset_hard(&wt_path, commit)?;
+            println!("  reset '{}' to {}", branch, base.unwrap_or("HEAD"));
+        }
+
+        if let Some(name) = &args.name {
+            names::insert(&root, name, branch, &wt_path)?;
+        }
 
-    let framework = if !no_sync {
-        let fw = sync::sync_worktree(&root, &wt_path, &config.sync)?;
+        if let Some(source) = &untracked_source {
+            sync::copy_untracked(source, &wt_path)?;
+        }
 
-        // Run post_start hook if configured
-        if let Some(hook) = &config.hooks.post_start {
-            println!("  running post_start hook...");
-            let status = Command::new("sh")
-                .args(["-c", hook])
-                .current_dir(&wt_path)
-                .status()?;
-            if !status.success() {
-                eprintln!("  warning: post_start hook exited with {}", status);
+        let mut config = config::load_worktree_config(&root, &wt_path)?;
+        if let Some(source) = &ignored_source {
+            sync::clone_ignored(source, &wt_path, &config.sync.symlink)?;
+        }
+        if args.install_cmd.is_some() {
+            config.sync.install_cmd = args.install_cmd.clone();
+        }
+        if let Some(jobs) = args.jobs {
+            config.sync.install_jobs = std::num::NonZeroUsize::new(jobs.into());
+        }
+        if args.quiet_install {
+            config.sync.quiet_install = true;
+        }
+
+        if let Some(rule) = config.git_config_for(branch) {
+            for (key, value) in &rule.set {
+                git::set_worktree_config(&wt_path, key, value)?;
+                println!("  git config {} = {} (matched '{}')", key, value, rule.pattern);
             }
         }
-        fw
-    } else {
-        sync::Framework::Unknown
-    };
+        if let Some(key) = &root_config.worktree.signing_key {
+            git::set_worktree_config(&wt_path, "user.signingkey", key)?;
+            git::set_worktree_config(&wt_path, "commit.gpgsign", "true")?;
+            println!("  commits signed with key {}", key);
+        }
+        if let Some(template) = &commit_template {
+            git::set_worktree_config(&wt_path, "commit.template", &template.to_string_lossy())?;
+            println!("  commit template: {}", template.display());
+        }
 
-    if isolated {
-        let iso = isolation::setup_isolation(
-            branch,
-            &wt_path,
-            config.isolation.port_range_size,
-            framework,
-        )?;
-        println!("  isolated environment:");
-        if iso.port_count > 1 {
-            println!("    PORT={}..{}            → .env.local", iso.port, iso.port_end);
+        if (args.submodules || config.worktree.init_submodules.unwrap_or(false)) && wt_path.join(".gitmodules").exists() {
+            println!("  initializing submodules...");
+            git::submodule_update(&wt_path)?;
+            println!("  submodules ready");
+        }
+
+        let framework = if !args.no_sync {
+            let fw = sync::sync_worktree(&root, &wt_path, &config)?;
+            let workz_env = hooks::env(&root, &wt_path, branch);
+            hooks::run("post_sync", &config.hooks.post_sync, &wt_path, &workz_env)?;
+            let start_env: Vec<_> = workz_env.into_iter().chain(args.env.iter().cloned()).collect();
+            hooks::run("post_start", &config.hooks.post_start, &wt_path, &start_env)?;
+            fw
         } else {
-            println!("    PORT={}                 → .env.local", iso.port);
+            sync::Framework::Unknown
+        };
+
+        if args.isolated {
+            let iso = isolation::setup_isolation(
+                branch,
+                &wt_path,
+                config.isolation.port_range_size,
+                framework,
+            )?;
+            println!("  isolated environment:");
+            if iso.port_count > 1 {
+                println!("    PORT={}..{}            → .env.local", iso.port, iso.port_end);
+            } else {
+                println!("    PORT={}                 → .env.local", iso.port);
+            }
+            println!("    DB_NAME={}", iso.db_name);
+            println!("    COMPOSE_PROJECT_NAME={}", iso.compose_project);
+            if framework != sync::Framework::Unknown {
+                println!("    framework={:?}", framework);
+            }
         }
-        println!("    DB_NAME={}", iso.db_name);
-        println!("    COMPOSE_PROJECT_NAME={}", iso.compose_project);
-        if framework != sync::Framework::Unknown {
-            println!("    framework={:?}", framework);
+
+        if args.docker || args.docker_build {
+            launch_docker(&wt_path, args.docker_build)?;
         }
+        Ok(())
+    };
+    if let Err(err) = setup() {
+        if args.rollback_on_error {
+            rollback_start(&root, &wt_path, branch, new_branch);
+        }
+        return Err(err);
     }
 
-    if docker {
-        launch_docker(&wt_path)?;
+    if args.open_in_tmux || root_config.worktree.open_in_tmux.unwrap_or(false) {
+        open_in_tmux(&wt_path, branch)?;
     }
 
-    if ai {
-        launch_ai_tool(ai_tool, &wt_path)?;
+    if args.ai {
+        launch_ai_tool(&args.ai_tool, &wt_path, args.detach_editor || args.ai_tool.is_gui())?;
     }
 
     println!("ready!");
-    println!("{}{}", CD_PREFIX, wt_path.display());
+    if args.shell {
+        return spawn_shell(&wt_path);
+    }
+    finish_start(&wt_path, cd);
     Ok(())
 }
 
-fn launch_ai_tool(tool: &AiTool, path: &std::path::Path) -> Result<()> {
+/// The last line of `start`/`new`: the cd sentinel, or the bare path.
+fn finish_start(wt_path: &std::path::Path, cd: bool) {
+    if cd {
+        print_cd(wt_path);
+    } else {
+        println!("{}", wt_path.display());
+    }
+}
+
+/// Undo a `start` whose setup failed: remove the worktree, its `--name`
+/// entry and port allocation, and the branch if `start` created it. Best effort — the original
+/// error is what gets reported.
+fn rollback_start(root: &std::path::Path, wt_path: &std::path::Path, branch: &str, new_branch: bool) {
+    eprintln!("  setup failed — rolling back (--rollback-on-error)");
+    if git::worktree_remove(wt_path, true).is_err() {
+        let _ = std::fs::remove_dir_all(wt_path);
+        let _ = git::worktree_prune();
+    }
+    let _ = names::forget_path(root, wt_path);
+    if isolation::get_allocation(branch).is_some_and(|a| std::path::Path::new(&a.worktree_path) == wt_path) {
+        let _ = isolation::release_isolation(branch);
+    }
+    if new_branch && git::branch_delete(branch, true).is_ok() {
+        eprintln!("  deleted branch '{}'", branch);
+    }
+    eprintln!("  removed {} — fix the error and rerun `workz start`", wt_path.display());
+}
+
+/// The branch `start` works on: the argument expanded through
+/// `worktree.branch_template`, unless that's off or the branch already exists.
+fn templated_branch(root: &std::path::Path, args: &StartArgs) -> Result<String> {
+    let input = match (&args.branch, args.from_pr) {
+        (Some(branch), Some(_)) => return Ok(branch.clone()),
+        (None, Some(pr)) => return Ok(format!("pr-{}", pr)),
+        (branch, None) => branch.as_deref().context("a branch name is required")?,
+    };
+    let worktree = config::load_config(root)?.worktree;
+    let Some(template) = worktree.branch_template.as_deref() else {
+        return Ok(input.to_string());
+    };
+    if args.no_template || git::branch_exists(input)? {
+        return Ok(input.to_string());
+    }
+    let user = git::config_value("user.name")
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default();
+    if template.contains("{user}") && user.trim().is_empty() {
+        bail!("worktree.branch_template uses {{user}}, but neither git user.name nor $USER is set");
+    }
+    let branch = worktree.expand_branch(input, &user);
+    if branch != input {
+        println!("  branch '{}' (from worktree.branch_template)", branch);
+    }
+    Ok(branch)
+}
+
+/// Whether git knows `path` as one of this repo's worktrees.
+fn is_registered_worktree(path: &std::path::Path) -> Result<bool> {
+    let path = path.canonicalize()?;
+    Ok(git::worktree_list()?
+        .iter()
+        .any(|wt| wt.path.canonicalize().is_ok_and(|p| p == path)))
+}
+
+/// Deal with a directory at the worktree path that git doesn't know about,
+/// usually left behind by an interrupted run. `git worktree add` accepts an
+/// empty one; anything else is deleted only with `--reuse` or a yes at the prompt.
+fn clear_stray_dir(path: &std::path::Path, reuse: bool) -> Result<()> {
+    let empty = std::fs::read_dir(path).map(|mut d| d.next().is_none()).unwrap_or(false);
+    if empty {
+        return Ok(());
+    }
+    let question = format!("{} exists but is not a worktree — delete it and recreate?", path.display());
+    if !reuse && !confirm(&question) {
+        bail!(
+            "{} exists but is not a worktree (left over from an interrupted run?) — rerun with --reuse to delete and recreate it, or move it out of the way",
+            path.display()
+        );
+    }
+    println!("  removing stray directory {}", path.display());
+    std::fs::remove_dir_all(path).with_context(|| format!("could not remove {}", path.display()))?;
+    Ok(())
+}
+
+/// The commit `start --reset-base` moves the existing `branch` to. Dropping
+/// commits needs a yes at the prompt or `--force`.
+fn reset_target(root: &std::path::Path, branch: &str, base: &str, force: bool) -> Result<String> {
+    let commit = git::rev_parse(base).with_context(|| format!("base '{}' not found", base))?;
+    let dropped = git::commits_ahead(root, &commit, branch)?;
+    if dropped == 0 || force {
+        return Ok(commit);
+    }
+    let question = format!("reset '{}' to {}, discarding {} commit(s)?", branch, base, dropped);
+    if !confirm(&question) {
+        bail!(
+            "'{}' has {} commit(s) not on {} — rerun with --force to discard them, or pick a new branch name",
+            branch,
+            dropped,
+            base
+        );
+    }
+    Ok(commit)
+}
+
+/// Fetch `remote`'s default branch and return it as a base, e.g. `origin/main`.
+fn remote_base(remote: &str) -> Result<String> {
+    let branch = git::remote_default_branch(remote)?;
+    println!("  fetching {}/{}...", remote, branch);
+    git::fetch_branch(remote, &branch)?;
+    Ok(format!("{}/{}", remote, branch))
+}
+
+/// Run an interactive `$SHELL` inside the worktree; exiting it returns to
+/// where `workz` was started. Bound to the terminal directly, since the shell
+/// wrapper captures workz's own stdout.
+fn spawn_shell(path: &std::path::Path) -> Result<()> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    println!("  entering {} in {} (exit to return)", shell, path.display());
+
+    let mut cmd = Command::new(&shell);
+    cmd.current_dir(path);
+    let tty = |write: bool| std::fs::OpenOptions::new().read(!write).write(write).open("/dev/tty");
+    if let (Ok(input), Ok(output), Ok(errors)) = (tty(false), tty(true), tty(true)) {
+        cmd.stdin(input).stdout(output).stderr(errors);
+    }
+
+    let status = cmd.status()?;
+    if !status.success() {
+        eprintln!("  warning: {} exited with {}", shell, status);
+    }
+    Ok(())
+}
+
+/// Start the tool in the worktree. Detached, it gets no stdio from us (its
+/// output would land in the wrapper's capture, next to the cd sentinel) and
+/// on Unix its own process group, so Ctrl-C or closing the terminal leaves it running.
+fn launch_ai_tool(tool: &AiTool, path: &std::path::Path, detach: bool) -> Result<()> {
     let path_str = path.to_str().unwrap_or(".");
 
     let (cmd, args): (&str, Vec<&str>) = match tool {
@@ -160,10 +523,15 @@ fn launch_ai_tool(tool: &AiTool, path: &std::path::Path) -> Result<()> {
 
     if which_exists(cmd) {
         println!("  launching {}...", tool);
-        Command::new(cmd)
-            .args(&args)
-            .current_dir(path)
-            .spawn()?;
+        let mut command = Command::new(cmd);
+        command.args(&args).current_dir(path);
+        if detach {
+            use std::process::Stdio;
+            command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        }
+        command.spawn()?;
     } else {
         eprintln!("  warning: '{}' not found in PATH, skipping", cmd);
     }
@@ -171,19 +539,41 @@ fn launch_ai_tool(tool: &AiTool, path: &std::path::Path) -> Result<()> {
     Ok(())
 }
 
-fn launch_docker(path: &std::path::Path) -> Result<()> {
-    // Check for compose file
-    let has_compose = path.join("docker-compose.yml").exists()
-        || path.join("docker-compose.yaml").exists()
-        || path.join("compose.yml").exists()
-        || path.join("compose.yaml").exists();
+/// Give the worktree its own tmux window named after the branch — in the
+/// current session when run inside tmux, else in a new detached session.
+fn open_in_tmux(path: &std::path::Path, branch: &str) -> Result<()> {
+    if !which_exists("tmux") {
+        eprintln!("  warning: 'tmux' not found in PATH, skipping");
+        return Ok(());
+    }
+    // tmux reads `.` and `:` in targets as window/pane separators
+    let name = git::safe_dir_name(branch).replace('.', "-");
+    let path_str = path.to_str().unwrap_or(".");
+    let inside = std::env::var_os("TMUX").is_some_and(|v| !v.is_empty());
+    let args = if inside {
+        vec!["new-window", "-c", path_str, "-n", &name]
+    } else {
+        vec!["new-session", "-d", "-s", &name, "-n", &name, "-c", path_str]
+    };
+    let status = Command::new("tmux").args(&args).status()?;
+    if !status.success() {
+        eprintln!("  warning: tmux {} exited with {}", args[0], status);
+    } else if inside {
+        println!("  opened tmux window '{}'", name);
+    } else {
+        println!("  started tmux session '{}' (tmux attach -t {})", name, name);
+    }
+    Ok(())
+}
 
-    if !has_compose {
+fn launch_docker(path: &std::path::Path, build: bool) -> Result<()> {
+    // Check for compose file
+    if !has_compose_file(path) {
         return Ok(());
     }
 
     // Prefer podman-compose, fall back to docker compose
-    let (cmd, args): (&str, Vec<&str>) = if which_exists("podman-compose") {
+    let (cmd, mut args): (&str, Vec<&str>) = if which_exists("podman-compose") {
         ("podman-compose", vec!["up", "-d"])
     } else if which_exists("docker") {
         ("docker", vec!["compose", "up", "-d"])
@@ -191,6 +581,9 @@ fn launch_docker(path: &std::path::Path) -> Result<()> {
         eprintln!("  warning: neither docker nor podman-compose found, skipping");
         return Ok(());
     };
+    if build {
+        args.push("--build");
+    }
 
     println!("  starting containers ({})...", cmd);
     let status = Command::new(cmd)
@@ -200,11 +593,41 @@ fn launch_docker(path: &std::path::Path) -> Result<()> {
 
     if !status.success() {
         eprintln!("  warning: {} compose up exited with {}", cmd, status);
+    } else if let Some(marker) = docker_marker(path) {
+        // Remember that we own these containers so `done` may stop them
+        if let Some(parent) = marker.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&marker, "")?;
     }
 
     Ok(())
 }
 
+/// Ask a yes/no question on the terminal (the shell wrapper captures stdio).
+/// Anything but an explicit yes — including no terminal — means no.
+fn confirm(question: &str) -> bool {
+    use std::io::{BufRead, BufReader, Write};
+
+    let Ok(mut tty) = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty") else {
+        return false;
+    };
+    if write!(tty, "{} [y/N] ", question).and_then(|_| tty.flush()).is_err() {
+        return false;
+    }
+    let mut answer = String::new();
+    if BufReader::new(tty).read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn has_compose_file(path: &std::path::Path) -> bool {
+    ["docker-compose.yml", "docker-compose.yaml", "compose.yml", "compose.yaml"]
+        .iter()
+        .any(|name| path.join(name).exists())
+}
+
 fn which_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
@@ -215,13 +638,131 @@ fn which_exists(cmd: &str) -> bool {
 
 // ── list ───────────────────────────────────────────────────────────────
 
-fn cmd_list() -> Result<()> {
-    let worktrees = git::worktree_list()?;
+/// Worktrees shown in interactive views — drops ones with the ignore marker unless `all`.
+fn visible_worktrees(all: bool) -> Result<Vec<git::Worktree>> {
+    let mut worktrees = git::worktree_list()?;
+    if !all {
+        let config = config::load_config(&git::repo_root()?)?;
+        worktrees.retain(|wt| !config.list.hides(&wt.path));
+    }
+    Ok(worktrees)
+}
 
+/// How `list` renders each worktree line.
+#[derive(Clone, Copy)]
+struct ListStyle {
+    /// Size bars and colored commit age (`--bars`)
+    bars: bool,
+    /// Measure worktree sizes; off with `--no-size` for a fast listing
+    sizes: bool,
+    /// Walk the whole tree for sizes (`--sizes`) instead of summing only the
+    /// top-level files; symlinked dirs such as shared node_modules count as 0
+    recursive_sizes: bool,
+}
+
+fn cmd_list(all: bool, style: ListStyle, watch: bool, by_type: bool) -> Result<()> {
+    if watch {
+        return list_watch(all, style, by_type);
+    }
+    let worktrees = visible_worktrees(all)?;
+    let lines = if by_type {
+        list_lines_by_type(&worktrees, style, &mut std::collections::HashMap::new())
+    } else {
+        list_lines(&worktrees, style)
+    };
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// `list_lines` under one header per detected project type, e.g. "Node + Python".
+/// Detection is cached per path in `cache` so `--watch` only sniffs each once.
+fn list_lines_by_type(
+    worktrees: &[git::Worktree],
+    style: ListStyle,
+    cache: &mut std::collections::HashMap<std::path::PathBuf, String>,
+) -> Vec<String> {
     if worktrees.is_empty() {
-        println!("no worktrees found");
-        return Ok(());
+        return list_lines(worktrees, style);
+    }
+    let mut groups: std::collections::BTreeMap<(bool, String), Vec<git::Worktree>> = Default::default();
+    for wt in worktrees {
+        let kind = cache
+            .entry(wt.path.clone())
+            .or_insert_with(|| {
+                if wt.is_bare {
+                    return String::new();
+                }
+                let types = sync::detect_project(&wt.path).types();
+                types.iter().map(ToString::to_string).collect::<Vec<_>>().join(" + ")
+            })
+            .clone();
+        // Unrecognised worktrees sort last
+        groups.entry((kind.is_empty(), kind)).or_default().push(wt.clone());
+    }
+
+    let mut lines = Vec::new();
+    for ((_, kind), group) in groups {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(if kind.is_empty() { "Other".to_string() } else { kind });
+        lines.extend(list_lines(&group, style));
+    }
+    lines
+}
+
+/// Re-render the list whenever a worktree's HEAD or dirty state changes.
+/// Polls every couple of seconds; runs until interrupted.
+fn list_watch(all: bool, style: ListStyle, by_type: bool) -> Result<()> {
+    use std::io::Write;
+
+    // The shell wrapper only shows output once workz exits; write to the
+    // terminal directly so updates appear as they happen
+    let tty = on_terminal()
+        .then(|| std::fs::OpenOptions::new().write(true).open("/dev/tty").ok())
+        .flatten();
+    let mut out: Box<dyn Write> = match tty {
+        Some(tty) => Box::new(tty),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let mut last = None;
+    let mut types = std::collections::HashMap::new();
+    loop {
+        let worktrees = visible_worktrees(all)?;
+        let state: Vec<_> = worktrees
+            .iter()
+            .map(|wt| {
+                let head = git::head_commit(&wt.path).ok();
+                let dirty = !wt.is_bare && git::is_dirty(&wt.path).unwrap_or(false);
+                (wt.path.clone(), wt.branch.clone(), head, dirty)
+            })
+            .collect();
+
+        if last.as_ref() != Some(&state) {
+            writeln!(out, "── {} ──", isolation::rfc3339_now())?;
+            let lines = if by_type {
+                list_lines_by_type(&worktrees, style, &mut types)
+            } else {
+                list_lines(&worktrees, style)
+            };
+            for line in lines {
+                writeln!(out, "{}", line)?;
+            }
+            out.flush()?;
+            last = Some(state);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(2));
     }
+}
+
+fn list_lines(worktrees: &[git::Worktree], style: ListStyle) -> Vec<String> {
+    if worktrees.is_empty() {
+        return vec!["no worktrees found".to_string()];
+    }
+    let mut lines = Vec::new();
 
     let max_branch = worktrees
         .iter()
@@ -229,34 +770,156 @@ fn cmd_list() -> Result<()> {
         .max()
         .unwrap_or(0);
 
-    for wt in &worktrees {
+    // Worktrees nested inside another are counted on their own line only
+    let roots: Vec<_> = if style.recursive_sizes {
+        worktrees.iter().filter_map(|w| w.path.canonicalize().ok()).collect()
+    } else {
+        Vec::new()
+    };
+    let measure = |path: &std::path::Path| match path.canonicalize() {
+        Ok(path) if style.recursive_sizes => disk_usage(&path, &roots, &mut Vec::new()),
+        _ => dir_size_shallow(path),
+    };
+    let sizes: Vec<Option<u64>> = worktrees
+        .iter()
+        .map(|wt| (style.sizes && !wt.is_bare).then(|| measure(&wt.path)))
+        .collect();
+    let bars = style.bars && fancy_output();
+    let max_size = sizes.iter().flatten().copied().max().unwrap_or(0);
+    let max_path = worktrees
+        .iter()
+        .map(|w| w.path.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    for (wt, size) in worktrees.iter().zip(&sizes) {
         let dirty = if !wt.is_bare && git::is_dirty(&wt.path).unwrap_or(false) {
             " [modified]"
         } else {
             ""
         };
 
-        let label = if wt.is_bare { " (bare)" } else { "" };
-        let size = if !wt.is_bare {
-            format!(" ({})", human_size(dir_size_shallow(&wt.path)))
+        let label = if wt.is_bare {
+            " (bare)"
+        } else if wt.prunable.is_some() {
+            " [prunable]"
         } else {
-            String::new()
+            ""
+        };
+        let locked = if wt.is_locked { " [locked]" } else { "" };
+        // Nothing when there's no upstream, or nothing to push or pull
+        let upstream = match (wt.is_bare, git::ahead_behind(&wt.path, "@{upstream}")) {
+            (false, Some((ahead, behind))) if ahead + behind > 0 => format!(" ↑{} ↓{}", ahead, behind),
+            _ => String::new(),
         };
 
-        println!(
-            "  {:<width$}  {}{}{}{}",
+        if bars {
+            let age = git::last_commit_relative(&wt.path).unwrap_or_default();
+            let color = git::last_commit_timestamp(&wt.path)
+                .map(age_color)
+                .unwrap_or(ANSI_DIM);
+            let (bar, size) = match size {
+                Some(bytes) => (size_bar(*bytes, max_size), human_size(*bytes)),
+                None => (" ".repeat(BAR_WIDTH), String::new()),
+            };
+            lines.push(format!(
+                "  {:<width$}  {:<path_width$}  {} {:>7}  {}{}{}{}{}{}{}",
+                wt.branch,
+                wt.path.display(),
+                bar,
+                size,
+                color,
+                age,
+                ANSI_RESET,
+                label,
+                locked,
+                dirty,
+                upstream,
+                width = max_branch,
+                path_width = max_path,
+            ));
+            continue;
+        }
+
+        let size = size
+            .map(|bytes| format!(" ({})", human_size(bytes)))
+            .unwrap_or_default();
+
+        lines.push(format!(
+            "  {:<width$}  {}{}{}{}{}{}",
             wt.branch,
             wt.path.display(),
             label,
+            locked,
             dirty,
+            upstream,
             size,
             width = max_branch,
-        );
+        ));
     }
 
-    Ok(())
+    lines
+}
+
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Whether to draw bars and colors: output goes to a terminal — directly, or
+/// via the shell wrapper, which captures it and sets WORKZ_TTY — and
+/// `NO_COLOR` is unset.
+fn fancy_output() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    on_terminal() && !no_color
+}
+
+/// Whether output ends up on a terminal, directly or through the shell wrapper.
+fn on_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal() || std::env::var_os("WORKZ_TTY").is_some_and(|v| v == "1")
+}
+
+/// Width of the size bars in `list --bars`, in cells.
+const BAR_WIDTH: usize = 12;
+
+/// A bar of `value` scaled against `max`, at eighth-of-a-cell resolution.
+/// Always `BAR_WIDTH` cells wide so columns line up.
+fn size_bar(value: u64, max: u64) -> String {
+    const PARTIAL: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let mut eighths = if max == 0 {
+        0
+    } else {
+        (value as u128 * (BAR_WIDTH * 8) as u128 / max as u128) as usize
+    };
+    if value > 0 && eighths == 0 {
+        eighths = 1; // keep tiny-but-nonzero visible
+    }
+
+    let mut bar = "█".repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        bar.push(PARTIAL[eighths % 8 - 1]);
+    }
+    let cells = eighths.div_ceil(8);
+    bar.push_str(&" ".repeat(BAR_WIDTH - cells));
+    bar
 }
 
+/// Green for commits in the last day, yellow within a week, dim beyond.
+fn age_color(timestamp: i64) -> &'static str {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    match now - timestamp {
+        age if age < 24 * 3600 => ANSI_GREEN,
+        age if age < 7 * 24 * 3600 => ANSI_YELLOW,
+        _ => ANSI_DIM,
+    }
+}
+
+/// Sum of the files directly in `path` — cheap, but blind to subdirectories;
+/// `list --sizes` uses `disk_usage` for the real figure.
 pub fn dir_size_shallow(path: &std::path::Path) -> u64 {
     let Ok(entries) = std::fs::read_dir(path) else {
         return 0;
@@ -290,19 +953,62 @@ pub fn human_size(bytes: u64) -> String {
 
 // ── switch ─────────────────────────────────────────────────────────────
 
-fn cmd_switch(query: Option<&str>) -> Result<()> {
-    let worktrees = git::worktree_list()?;
+/// Plain `switch` only navigates. With `create`, a query that names no existing
+/// worktree branch falls through to `start`, which creates, syncs, and cds.
+fn cmd_switch(
+    query: Option<&str>,
+    create: bool,
+    all: bool,
+    picker: config::SwitchConfig,
+    no_tui: bool,
+    multi: Option<AiTool>,
+) -> Result<Outcome> {
+    let worktrees = visible_worktrees(all)?;
+
+    if let (true, Some(branch)) = (create, query) {
+        let root = git::repo_root()?;
+        let named = names::resolve(&root, branch).map(|entry| entry.path);
+        if let Some(path) = named.filter(|p| p.exists()) {
+            switch_to(&root, &path)?;
+            return Ok(Outcome::Success);
+        }
+        if let Some(wt) = worktrees.iter().find(|w| !w.is_bare && w.branch == branch) {
+            switch_to(&root, &wt.path)?;
+            return Ok(Outcome::Success);
+        }
+        let args = StartArgs { branch: Some(branch.to_string()), ..Default::default() };
+        return cmd_start(&args, true).map(Into::into);
+    }
 
-    let candidates: Vec<_> = worktrees.iter().filter(|w| !w.is_bare).collect();
+    let mut candidates: Vec<_> = worktrees.iter().filter(|w| !w.is_bare).collect();
+
+    // Switching to where you already are is noise — unless it's all there is
+    if let Some(here) = git::current_worktree() {
+        let elsewhere: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|w| w.path.canonicalize().ok().as_ref() != Some(&here))
+            .collect();
+        if !elsewhere.is_empty() {
+            candidates = elsewhere;
+        }
+    }
 
     if candidates.is_empty() {
         println!("no worktrees to switch to");
-        return Ok(());
+        return Ok(Outcome::Success);
     }
 
+    // Most frecent first; never-visited worktrees keep their order at the end
+    let ranked = history::ranked_paths();
+    candidates.sort_by_key(|wt| ranked.iter().position(|p| *p == wt.path).unwrap_or(usize::MAX));
+
     if candidates.len() == 1 {
-        println!("{}{}", CD_PREFIX, candidates[0].path.display());
-        return Ok(());
+        if let Some(editor) = &multi {
+            launch_ai_tool(editor, &candidates[0].path, true)?;
+        }
+        switch_to(&git::repo_root()?, &candidates[0].path)?;
+        return Ok(Outcome::Success);
     }
 
     // Build display lines: "branch  ->  /path"
@@ -314,48 +1020,336 @@ fn cmd_switch(query: Option<&str>) -> Result<()> {
     let input = items.join("\n");
 
     let query_string = query.map(|s| s.to_string());
-    let options = SkimOptionsBuilder::default()
-        .height(Some("40%"))
-        .multi(false)
-        .reverse(true)
+    let picker = config::load_config(&git::repo_root()?)?.switch.overridden_by(picker);
+    let match_mode = picker.match_mode.unwrap_or(config::MatchMode::Fuzzy);
+    if no_tui || !tui_available() {
+        if multi.is_some() {
+            bail!("--multi needs the interactive picker (no terminal, or --no-tui given)");
+        }
+        return switch_without_tui(&candidates, query, match_mode);
+    }
+    let options = match SkimOptionsBuilder::default()
+        .height(Some(picker.height()?))
+        .multi(multi.is_some())
+        .reverse(picker.reverse.unwrap_or(true))
+        .exact(match_mode == config::MatchMode::Exact)
+        .regex(match_mode == config::MatchMode::Regex)
+        .preview(picker.preview.as_deref())
         .prompt(Some("switch> "))
         .query(query_string.as_deref())
         .build()
-        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("  warning: picker unavailable ({}), matching without it", e);
+            return switch_without_tui(&candidates, query, match_mode);
+        }
+    };
 
     let item_reader = SkimItemReader::default();
     let items = item_reader.of_bufread(Cursor::new(input));
 
     let output = Skim::run_with(&options, Some(items));
 
-    let selected = match output {
+    let selected: Vec<String> = match output {
         Some(out) if !out.is_abort && !out.selected_items.is_empty() => {
-            out.selected_items[0].output().to_string()
+            out.selected_items.iter().map(|item| item.output().to_string()).collect()
         }
         _ => {
             println!("cancelled");
-            return Ok(());
+            return Ok(Outcome::Cancelled);
+        }
+    };
+
+    // Parse the paths from "branch\t/path"
+    let paths: Vec<_> = selected
+        .iter()
+        .map(|line| std::path::PathBuf::from(line.split('\t').nth(1).unwrap_or(line).trim()))
+        .collect();
+
+    // Every pick opens in the editor; the shell can only follow one of them
+    if let Some(editor) = &multi {
+        for path in &paths {
+            launch_ai_tool(editor, path, true)?;
+        }
+    }
+    switch_to(&git::repo_root()?, &paths[0])?;
+    Ok(Outcome::Success)
+}
+
+/// Whether skim can take over the terminal: it draws on and reads keys from
+/// the controlling tty, which containers and CI jobs often don't have.
+fn tui_available() -> bool {
+    #[cfg(unix)]
+    {
+        std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty").is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::IsTerminal;
+        std::io::stdin().is_terminal()
+    }
+}
+
+/// `switch` without the picker: go to the best match for `query`, or, with no
+/// query, print the candidates so the user can rerun with one.
+fn switch_without_tui(
+    candidates: &[&git::Worktree],
+    query: Option<&str>,
+    mode: config::MatchMode,
+) -> Result<Outcome> {
+    let lines: Vec<String> =
+        candidates.iter().map(|wt| format!("{}\t{}", wt.branch, wt.path.display())).collect();
+
+    let Some(query) = query.filter(|q| !q.is_empty()) else {
+        for wt in candidates {
+            println!("  {}  {}", wt.branch, wt.path.display());
+        }
+        eprintln!("  no terminal for the picker — rerun with a query, e.g. `workz switch <branch>`");
+        return Ok(Outcome::Cancelled);
+    };
+
+    let ranked = matcher::rank(&lines, query, mode)?;
+    let Some((&best, rest)) = ranked.split_first() else {
+        bail!("no worktree matches '{}'", query);
+    };
+    if !rest.is_empty() {
+        let others: Vec<_> = rest.iter().map(|&i| candidates[i].branch.as_str()).collect();
+        eprintln!("  also matched: {}", others.join(", "));
+    }
+    switch_to(&git::repo_root()?, &candidates[best].path)?;
+    Ok(Outcome::Success)
+}
+
+/// Tell the shell wrapper to cd into `path`, then to run any post_switch hooks
+/// there. The hooks go through the wrapper because only the interactive shell
+/// itself can be changed (`direnv reload`, `source .venv/bin/activate`).
+fn switch_to(root: &std::path::Path, path: &std::path::Path) -> Result<()> {
+    print_cd(path);
+    let config = config::load_worktree_config(root, path)?;
+    let Some(token) = run_token() else {
+        if config.hooks.post_switch.is_some() {
+            eprintln!("  note: post_switch hooks need the shell wrapper (eval \"$(workz init zsh)\")");
+        }
+        return Ok(());
+    };
+    for cmd in config.hooks.post_switch.iter().flat_map(config::Hook::commands) {
+        if cmd.contains('\n') {
+            eprintln!("  warning: skipping multi-line post_switch hook (use one line per command)");
+            continue;
+        }
+        println!("{}{}:{}", RUN_PREFIX, token, cmd);
+    }
+    Ok(())
+}
+
+/// Tell the shell wrapper to cd into `path`, and count the visit for
+/// `recent`/`switch` ordering. History errors never block the cd.
+fn print_cd(path: &std::path::Path) {
+    println!("{}{}", CD_PREFIX, path.display());
+    let _ = history::record_access(path);
+}
+
+// ── recent ─────────────────────────────────────────────────────────────
+
+/// This repo's worktrees, most frecent first; never-visited ones are left out.
+fn cmd_recent() -> Result<()> {
+    let worktrees: Vec<_> = git::worktree_list()?.into_iter().filter(|w| !w.is_bare).collect();
+    let visits = history::load();
+    let now = history::now();
+    let ranked: Vec<_> = history::ranked_paths()
+        .into_iter()
+        .filter_map(|path| worktrees.iter().find(|wt| wt.path == path))
+        .filter_map(|wt| Some((wt, *visits.get(&wt.path)?)))
+        .collect();
+
+    if ranked.is_empty() {
+        println!("no visits recorded yet — `workz switch` and `workz start` count as visits");
+        return Ok(());
+    }
+    let width = ranked.iter().map(|(wt, _)| wt.branch.len()).max().unwrap_or(0);
+    for (wt, v) in ranked {
+        println!(
+            "{:<width$}  {:>6.1}  {:<12}  {}",
+            wt.branch,
+            v.score(now),
+            ago(now.saturating_sub(v.last)),
+            wt.path.display(),
+            width = width
+        );
+    }
+    Ok(())
+}
+
+fn ago(secs: u64) -> String {
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+// ── which ──────────────────────────────────────────────────────────────
+
+/// Script-friendly existence check: prints only the path, so
+/// `dir=$(workz which feat)` works and a missing worktree fails the command.
+fn cmd_which(branch: &str) -> Result<()> {
+    let wt = git::worktree_list()?
+        .into_iter()
+        .find(|wt| wt.branch == branch);
+    match wt {
+        Some(wt) => {
+            println!("{}", wt.path.display());
+            Ok(())
+        }
+        None => bail!("no worktree for branch '{}'", branch),
+    }
+}
+
+// ── owners ─────────────────────────────────────────────────────────────
+
+/// The inverse of `workz_conflicts`: for each file, the worktrees whose
+/// uncommitted changes touch it.
+fn cmd_owners(files: &[std::path::PathBuf]) -> Result<()> {
+    let worktrees: Vec<_> = git::worktree_list()?.into_iter().filter(|w| !w.is_bare).collect();
+    let cwd = std::env::current_dir()?;
+    let modified: Vec<_> = worktrees
+        .iter()
+        .map(|wt| (wt, git::modified_files(&wt.path).unwrap_or_default()))
+        .collect();
+
+    let mut owned = 0;
+    let mut owners = std::collections::BTreeSet::new();
+    for file in files {
+        let rel = repo_relative(&cwd.join(file), &worktrees).unwrap_or_else(|| file.to_string_lossy().into_owned());
+        let hits: Vec<_> = modified
+            .iter()
+            .filter(|(_, changed)| changed.iter().any(|entry| status_entry_covers(entry, &rel)))
+            .map(|(wt, _)| *wt)
+            .collect();
+        if hits.is_empty() {
+            println!("{}  (not modified in any worktree)", rel);
+            continue;
+        }
+        owned += 1;
+        println!("{}", rel);
+        let width = hits.iter().map(|wt| wt.branch.len()).max().unwrap_or(0);
+        for wt in hits {
+            println!("  {:<width$}  {}", wt.branch, wt.path.display(), width = width);
+            owners.insert(&wt.branch);
+        }
+    }
+    if files.len() > 1 {
+        println!("\n{} of {} file(s) modified, across {} worktree(s)", owned, files.len(), owners.len());
+    }
+    Ok(())
+}
+
+/// `path` relative to the worktree containing it (the deepest one, since
+/// worktrees may be nested), with `.`/`..` resolved lexically.
+fn repo_relative(path: &std::path::Path, worktrees: &[git::Worktree]) -> Option<String> {
+    use std::path::Component;
+
+    let mut clean = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                clean.pop();
+            }
+            Component::CurDir => {}
+            other => clean.push(other),
+        }
+    }
+    worktrees
+        .iter()
+        .filter_map(|wt| clean.strip_prefix(&wt.path).ok())
+        .min_by_key(|rel| rel.components().count())
+        .map(|rel| rel.to_string_lossy().into_owned())
+}
+
+/// Whether a `git status --porcelain` path covers `file`: the same path, the
+/// new side of a rename, or an untracked directory containing it.
+fn status_entry_covers(entry: &str, file: &str) -> bool {
+    let entry = entry.rsplit(" -> ").next().unwrap_or(entry).trim_matches('"');
+    entry == file || (entry.ends_with('/') && file.starts_with(entry))
+}
+
+// ── diff ───────────────────────────────────────────────────────────────
+
+fn cmd_diff(a: &str, b: Option<&str>, working: bool) -> Result<()> {
+    let root = git::repo_root()?;
+    let (a_path, a_branch) = find_worktree(&root, a)?;
+    let (b_path, b_branch) = match b {
+        Some(b) => find_worktree(&root, b)?,
+        None => {
+            let here = git::toplevel()?;
+            let branch = git::current_branch(&here)?;
+            (here, branch)
         }
     };
 
-    // Parse the path from "branch\t/path"
-    let path = selected
-        .split('\t')
-        .nth(1)
-        .unwrap_or(&selected)
-        .trim();
+    // With --working, diff a's state (as a dangling stash commit) against b's
+    // working tree; both worktrees share one object store.
+    let mut args = vec!["diff".to_string()];
+    if working {
+        args.push(git::working_state(&a_path)?);
+    } else {
+        if a_branch.is_empty() || b_branch.is_empty() {
+            bail!("a worktree has a detached HEAD — use --working to compare its checkout");
+        }
+        args.push(a_branch);
+        args.push(b_branch);
+    }
 
-    println!("{}{}", CD_PREFIX, path);
+    let mut cmd = Command::new("git");
+    cmd.args(&args).current_dir(&b_path);
+    // The shell wrapper captures stdout; hand git the terminal so it can page.
+    if on_terminal() {
+        if let Ok(tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+            cmd.stdout(tty);
+        }
+    }
+    let status = cmd.status().context("failed to execute git")?;
+    if !status.success() {
+        bail!("git diff exited with {}", status);
+    }
     Ok(())
 }
 
+/// Path and branch of the worktree known by `name`: a `start --name`/`adopt`
+/// name first, then a checked-out branch.
+fn find_worktree(root: &std::path::Path, name: &str) -> Result<(std::path::PathBuf, String)> {
+    if let Some(entry) = names::resolve(root, name) {
+        return Ok((entry.path, entry.branch));
+    }
+    match git::worktree_list()?.into_iter().find(|wt| wt.branch == name) {
+        Some(wt) => Ok((wt.path, wt.branch)),
+        None => bail!("no worktree for '{}'", name),
+    }
+}
+
 // ── done ───────────────────────────────────────────────────────────────
 
-fn cmd_done(branch: Option<&str>, force: bool, delete_branch: bool, cleanup_db: bool) -> Result<()> {
+/// `globals` are the global flags (`--log-file`, `--verbose`, ...) to pass on
+/// to the child runs of `done --all --parallel`.
+fn cmd_done(args: &DoneArgs, globals: &[std::ffi::OsString]) -> Result<Outcome> {
+    if args.all {
+        return cmd_done_all(args, globals);
+    }
+
     let root = git::repo_root()?;
 
-    let (wt_path, branch_name) = if let Some(b) = branch {
-        (git::worktree_path(&root, b), b.to_string())
+    let (wt_path, branch_name) = if let Some(b) = args.branch.as_deref() {
+        match names::resolve(&root, b) {
+            Some(entry) => (entry.path, entry.branch),
+            None => {
+                // A worktree made with `start --name` lives outside the default path
+                let listed = git::worktree_list()?.into_iter().find(|wt| wt.branch == b);
+                let path = listed.map(|wt| wt.path).unwrap_or_else(|| git::worktree_path(&root, b));
+                (path, b.to_string())
+            }
+        }
     } else {
         let cwd = std::env::current_dir()?;
         let branch_name = git::current_branch(&cwd)?;
@@ -371,55 +1365,307 @@ fn cmd_done(branch: Option<&str>, force: bool, delete_branch: bool, cleanup_db:
         bail!("worktree not found at {}", wt_path.display());
     }
 
-    if !force && git::is_dirty(&wt_path).unwrap_or(false) {
+    if !args.force && git::is_dirty(&wt_path).unwrap_or(false) {
         bail!("worktree has uncommitted changes — use --force to remove anyway");
     }
 
+    remove_worktree(&root, &wt_path, &branch_name, args)?;
+
+    println!("done!");
+    Ok(Outcome::Success)
+}
+
+/// Remove every worktree that is safe to drop: clean, merged into the default
+/// branch, and with nothing unpushed. The main and current worktrees are never
+/// candidates.
+fn cmd_done_all(args: &DoneArgs, globals: &[std::ffi::OsString]) -> Result<Outcome> {
+    let root = git::repo_root()?;
+    let main = root.canonicalize().unwrap_or_else(|_| root.clone());
+    let current = git::toplevel().ok().and_then(|p| p.canonicalize().ok());
+    let base = git::default_branch();
+
+    let mut eligible = Vec::new();
+    for wt in git::worktree_list()? {
+        let path = wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone());
+        if wt.is_bare || path == main {
+            continue;
+        }
+        let reason = if current.as_ref() == Some(&path) {
+            Some("current worktree".to_string())
+        } else if wt.is_detached {
+            Some("detached HEAD".to_string())
+        } else if wt.branch == base {
+            Some("default branch".to_string())
+        } else if git::is_dirty(&wt.path).unwrap_or(true) {
+            Some("uncommitted changes".to_string())
+        } else if !git::is_merged(&wt.branch, &base).unwrap_or(false) {
+            Some(format!("not merged into {}", base))
+        } else if has_unpushed_commits(&root, &wt.branch) {
+            Some("unpushed commits".to_string())
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => println!("  skipping {} ({})", wt.branch, reason),
+            None => eligible.push(wt),
+        }
+    }
+
+    if eligible.is_empty() {
+        println!("no worktrees are safe to remove");
+        return Ok(Outcome::Success);
+    }
+
+    println!("will remove:");
+    for wt in &eligible {
+        println!("  {} ({})", wt.branch, wt.path.display());
+    }
+    if !args.yes && !confirm(&format!("remove {} worktree(s)?", eligible.len())) {
+        println!("cancelled");
+        return Ok(Outcome::Cancelled);
+    }
+
+    if args.parallel > 1 {
+        return done_parallel(&eligible, args, globals);
+    }
+
+    for wt in &eligible {
+        if let Err(e) = remove_worktree(&root, &wt.path, &wt.branch, args) {
+            eprintln!("  warning: could not remove {}: {}", wt.branch, e);
+        }
+    }
+
+    println!("done!");
+    Ok(Outcome::Success)
+}
+
+/// Remove worktrees concurrently. Each removal runs as its own `workz done`
+/// so hook and container output can be captured and printed per worktree.
+fn done_parallel(worktrees: &[git::Worktree], args: &DoneArgs, globals: &[std::ffi::OsString]) -> Result<Outcome> {
+    if args.prune_remote && !args.yes {
+        bail!("--prune-remote with --parallel needs --yes (prompts can't run concurrently)");
+    }
+
+    let exe = std::env::current_exe()?;
+    let mut flags = vec!["--yes"];
+    if args.delete_branch {
+        flags.push("--delete-branch");
+    }
+    if args.cleanup_db {
+        flags.push("--cleanup-db");
+    }
+    if args.prune_remote {
+        flags.push("--prune-remote");
+    }
+
+    let results = parallel::map(worktrees.iter().collect(), args.parallel, |wt| {
+        let output = Command::new(&exe)
+            .args(globals)
+            .arg("done")
+            .arg(&wt.branch)
+            .args(&flags)
+            .output();
+        (wt, output)
+    });
+
+    let mut failed = Vec::new();
+    for (wt, output) in results {
+        println!("\n  [{}]", wt.branch);
+        match output {
+            Ok(out) => {
+                let text = String::from_utf8_lossy(&out.stdout);
+                let errors = String::from_utf8_lossy(&out.stderr);
+                for line in text.lines().chain(errors.lines()) {
+                    println!("    {}", line);
+                }
+                if !out.status.success() {
+                    failed.push(wt.branch.as_str());
+                }
+            }
+            Err(e) => {
+                println!("    error: {}", e);
+                failed.push(wt.branch.as_str());
+            }
+        }
+    }
+
+    println!();
+    println!("removed {} of {} worktree(s)", worktrees.len() - failed.len(), worktrees.len());
+    if !failed.is_empty() {
+        bail!("could not remove: {}", failed.join(", "));
+    }
+    Ok(Outcome::Success)
+}
+
+/// Whether `branch` has commits its upstream doesn't. No upstream means nothing
+/// to compare against, which counts as pushed.
+fn has_unpushed_commits(root: &std::path::Path, branch: &str) -> bool {
+    let Some((remote, remote_branch)) = git::upstream(branch) else {
+        return false;
+    };
+    let upstream = format!("{}/{}", remote, remote_branch);
+    git::commits_ahead(root, &upstream, branch).unwrap_or(0) > 0
+}
+
+/// Tear down one worktree: containers, isolation, pre_done hook, the worktree
+/// itself, and optionally its local/remote branch.
+fn remove_worktree(
+    root: &std::path::Path,
+    wt_path: &std::path::Path,
+    branch_name: &str,
+    args: &DoneArgs,
+) -> Result<()> {
+    let force = args.force;
+
+    // Removal never touches stashes, but they're easy to forget once the
+    // worktree they belonged to is gone
+    let stashes = git::stashes_on(root, branch_name).unwrap_or_default();
+    if !stashes.is_empty() {
+        println!("  note: {} stash(es) made on '{}' stay behind (git stash list):", stashes.len(), branch_name);
+        for stash in &stashes {
+            println!("    {}", stash);
+        }
+    }
+
+    // Decide up front whether the branch goes too, so a declined prompt
+    // doesn't leave things half torn down
+    let mut delete_branch = args.delete_branch || args.prune_remote;
+    // Check before tearing anything down, so a refusal leaves the worktree intact
+    if delete_branch {
+        git::ensure_deletable(branch_name)?;
+    }
+    let mut force_branch = force;
+    if delete_branch && !force {
+        let base = git::default_branch();
+        if git::is_merged(branch_name, &base).unwrap_or(false) {
+            // Merged into the base even if not into HEAD — `-d` would balk
+            force_branch = true;
+        } else if args.yes
+            || confirm(&format!(
+                "branch '{}' isn't merged into {} — delete it anyway?",
+                branch_name, base
+            ))
+        {
+            force_branch = true;
+        } else {
+            println!("  keeping unmerged branch '{}'", branch_name);
+            delete_branch = false;
+        }
+    }
+
     // Stop containers if docker-compose exists
-    stop_docker(&wt_path);
+    stop_docker(wt_path);
 
     // Release isolated port allocation (no-op if not isolated)
-    let _ = isolation::release_isolation(&branch_name);
+    let _ = isolation::release_isolation(branch_name);
 
-    if cleanup_db {
-        isolation::drop_database(&branch_name);
+    if args.cleanup_db {
+        isolation::drop_database(branch_name);
     }
 
-    // Run pre_done hook if configured
-    let config = config::load_config(&root)?;
-    if let Some(hook) = &config.hooks.pre_done {
-        println!("  running pre_done hook...");
-        let status = Command::new("sh")
-            .args(["-c", hook])
-            .current_dir(&wt_path)
-            .status()?;
-        if !status.success() {
-            eprintln!("  warning: pre_done hook exited with {}", status);
-        }
+    // Run pre_done hooks if configured
+    let config = config::load_worktree_config(root, wt_path)?;
+    let ticket = config.done.ticket(branch_name);
+    // Resolved now: post_done runs after the worktree directory is gone
+    let workz_env = hooks::env(root, wt_path, branch_name);
+    run_done_hooks("pre_done", &config.hooks.pre_done, wt_path, branch_name, ticket.as_deref(), &workz_env)?;
+
+    if config.done.backup_untracked.unwrap_or(false) {
+        backup_before_done(root, wt_path, branch_name, &config.sync.symlink)?;
     }
 
     println!("removing worktree at {}", wt_path.display());
-    git::worktree_remove(&wt_path, force)?;
+    git::worktree_remove(wt_path, force)?;
+    let _ = names::forget_path(root, wt_path);
+    run_done_hooks("post_done", &config.hooks.post_done, root, branch_name, ticket.as_deref(), &workz_env)?;
+
+    // Look up the upstream before the local branch (and its config) is gone.
+    // Branches pushed without -u have none; fall back to the push remote.
+    let upstream = if args.prune_remote {
+        git::upstream(branch_name).or_else(|| {
+            let remote = config.remotes.push();
+            git::remote_branch_exists(remote, branch_name)
+                .then(|| (remote.to_string(), branch_name.to_string()))
+        })
+    } else {
+        None
+    };
 
     if delete_branch {
         println!("deleting branch '{}'", branch_name);
-        git::branch_delete(&branch_name, force)?;
+        git::branch_delete(branch_name, force_branch)?;
+    }
+
+    if delete_branch && args.prune_remote {
+        match upstream {
+            None => println!("  no upstream for '{}', skipping remote delete", branch_name),
+            Some((remote, remote_branch)) => {
+                let target = format!("{}/{}", remote, remote_branch);
+                if args.yes || confirm(&format!("delete remote branch {}?", target)) {
+                    println!("deleting remote branch {}", target);
+                    git::push_delete(&remote, &remote_branch)?;
+                } else {
+                    println!("  kept remote branch {}", target);
+                }
+            }
+        }
     }
 
-    println!("done!");
     Ok(())
 }
 
-fn stop_docker(path: &std::path::Path) {
-    let has_compose = path.join("docker-compose.yml").exists()
-        || path.join("docker-compose.yaml").exists()
-        || path.join("compose.yml").exists()
-        || path.join("compose.yaml").exists();
+/// `done.backup_untracked`: save what removal would destroy for good under
+/// `<state dir>/workz/backups/<repo>--<branch>-<time>`. Nothing is written
+/// when there's nothing to save.
+fn backup_before_done(root: &std::path::Path, wt_path: &std::path::Path, branch: &str, skip: &[String]) -> Result<()> {
+    let stamp = isolation::rfc3339_now().replace(':', "-");
+    let dest = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .context("no state directory for done.backup_untracked")?
+        .join("workz")
+        .join("backups")
+        .join(format!("{}--{}-{}", git::repo_name(root), git::safe_dir_name(branch), stamp));
+    if sync::backup_untracked(wt_path, &dest, skip)? == 0 {
+        println!("  no untracked files to back up");
+    }
+    Ok(())
+}
+
+/// Run done hooks in `dir` with `{branch}` and `{ticket}` filled in. Commands
+/// that use `{ticket}` are skipped when the branch carries none.
+fn run_done_hooks(
+    name: &str,
+    hook: &Option<config::Hook>,
+    dir: &std::path::Path,
+    branch: &str,
+    ticket: Option<&str>,
+    env: &[(String, String)],
+) -> Result<()> {
+    for cmd in hook.iter().flat_map(config::Hook::commands) {
+        let cmd = match ticket {
+            Some(ticket) => cmd.replace("{ticket}", ticket),
+            None if cmd.contains("{ticket}") => continue,
+            None => cmd.clone(),
+        };
+        let cmd = cmd.replace("{branch}", branch);
+
+        hooks::run_command(name, &cmd, dir, env)?;
+    }
+    Ok(())
+}
 
-    if !has_compose {
+fn stop_docker(path: &std::path::Path) {
+    if !has_compose_file(path) {
         return;
     }
 
+    // Only tear down stacks that `workz start --docker` brought up
+    let Some(marker) = docker_marker(path).filter(|m| m.exists()) else {
+        println!("  containers were not started by workz — leaving them running");
+        println!("  (run `docker compose down` in the worktree to stop them)");
+        return;
+    };
+
     let (cmd, args): (&str, Vec<&str>) = if which_exists("podman-compose") {
         ("podman-compose", vec!["down"])
     } else if which_exists("docker") {
@@ -429,10 +1675,24 @@ fn stop_docker(path: &std::path::Path) {
     };
 
     println!("  stopping containers...");
-    let _ = Command::new(cmd)
+    let stopped = Command::new(cmd)
         .args(&args)
         .current_dir(path)
-        .status();
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+
+    if stopped {
+        let _ = std::fs::remove_file(marker);
+    }
+}
+
+/// Marker recording that workz started a worktree's containers:
+/// `.git/workz/docker/<worktree dir name>` in the main repo.
+fn docker_marker(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let common = git::common_dir(path).ok()?;
+    let name = path.file_name()?;
+    Some(common.join("workz").join("docker").join(name))
 }
 
 // ── sync ───────────────────────────────────────────────────────────────
@@ -445,9 +1705,105 @@ fn cmd_sync() -> Result<()> {
         bail!("you're in the main worktree — switch to a worktree first");
     }
 
-    let config = config::load_config(&root)?;
+    let config = config::load_worktree_config(&root, &cwd)?;
     println!("syncing worktree at {}", cwd.display());
-    let _framework = sync::sync_worktree(&root, &cwd, &config.sync)?;
+    let _framework = sync::sync_worktree(&root, &cwd, &config)?;
+    let branch = git::current_branch(&cwd).unwrap_or_default();
+    hooks::run("post_sync", &config.hooks.post_sync, &cwd, &hooks::env(&root, &cwd, &branch))?;
+    println!("done!");
+    Ok(())
+}
+
+// ── lock ───────────────────────────────────────────────────────────────
+
+fn cmd_lock(name: &str, reason: Option<&str>) -> Result<()> {
+    let root = git::repo_root()?;
+    let (path, branch) = find_worktree(&root, name)?;
+    git::worktree_lock(&path, reason)?;
+    match reason {
+        Some(reason) => println!("locked '{}' ({})", branch, reason),
+        None => println!("locked '{}'", branch),
+    }
+    Ok(())
+}
+
+fn cmd_unlock(name: &str) -> Result<()> {
+    let root = git::repo_root()?;
+    let (path, branch) = find_worktree(&root, name)?;
+    git::worktree_unlock(&path)?;
+    println!("unlocked '{}'", branch);
+    Ok(())
+}
+
+// ── move ───────────────────────────────────────────────────────────────
+
+fn cmd_move(name: &str, new_path: &std::path::Path) -> Result<()> {
+    let root = git::repo_root()?;
+    let (from, branch) = find_worktree(&root, name)?;
+    if from.canonicalize().ok() == root.canonicalize().ok() {
+        bail!("'{}' is the main worktree — git can't move it", branch);
+    }
+    if new_path.exists() || new_path.symlink_metadata().is_ok() {
+        bail!("{} already exists — pick a path that doesn't", new_path.display());
+    }
+    let to = std::path::absolute(new_path)?;
+    let (Some(parent), Some(dir_name)) = (to.parent(), to.file_name()) else {
+        bail!("{} is not a usable worktree path", new_path.display());
+    };
+    std::fs::create_dir_all(parent).with_context(|| format!("could not create {}", parent.display()))?;
+    let to = parent.canonicalize()?.join(dir_name);
+
+    println!("moving '{}' from {} to {}", branch, from.display(), to.display());
+    git::worktree_move(&from, &to)?;
+    names::relocate(&root, &from, &to)?;
+    isolation::relocate_isolation(&branch, &to)?;
+
+    // Relative links made outside workz now point nowhere; drop them so the
+    // resync below can put fresh ones in their place
+    for entry in std::fs::read_dir(&to)?.flatten() {
+        let link = entry.path();
+        if entry.file_type().is_ok_and(|t| t.is_symlink()) && !link.exists() {
+            std::fs::remove_file(&link)?;
+            println!("  removed broken link {}", link.file_name().unwrap_or_default().to_string_lossy());
+        }
+    }
+    let config = config::load_worktree_config(&root, &to)?;
+    sync::sync_worktree(&root, &to, &config)?;
+
+    println!("done!");
+    print_cd(&to);
+    Ok(())
+}
+
+// ── adopt ──────────────────────────────────────────────────────────────
+
+/// Sync deps/env into a worktree workz didn't create and register it in the
+/// name index, so `switch -c`/`done` accept its directory name.
+fn cmd_adopt(path: &std::path::Path) -> Result<()> {
+    let root = git::repo_root()?;
+    let target = path
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", path.display()))?;
+
+    let wt = git::worktree_list()?
+        .into_iter()
+        .find(|wt| wt.path.canonicalize().ok().as_ref() == Some(&target));
+    let Some(wt) = wt else {
+        bail!("{} is not a worktree of this repository", target.display());
+    };
+    if wt.is_bare || root.canonicalize().ok().as_ref() == Some(&target) {
+        bail!("{} is the main worktree — nothing to adopt", target.display());
+    }
+
+    println!("adopting '{}' at {}", wt.branch, target.display());
+    let config = config::load_worktree_config(&root, &target)?;
+    sync::sync_worktree(&root, &target, &config)?;
+    hooks::run("post_sync", &config.hooks.post_sync, &target, &hooks::env(&root, &target, &wt.branch))?;
+
+    let name = target.file_name().unwrap_or_default().to_string_lossy().to_string();
+    names::insert(&root, &name, &wt.branch, &target)?;
+    println!("  registered as '{}'", name);
+
     println!("done!");
     Ok(())
 }
@@ -476,11 +1832,7 @@ fn cmd_status() -> Result<()> {
             .map(|t| format!("  {}", t))
             .unwrap_or_default();
 
-        let has_compose = wt.path.join("docker-compose.yml").exists()
-            || wt.path.join("docker-compose.yaml").exists()
-            || wt.path.join("compose.yml").exists()
-            || wt.path.join("compose.yaml").exists();
-        let docker = if has_compose { "  [docker]" } else { "" };
+        let docker = if has_compose_file(&wt.path) { "  [docker]" } else { "" };
 
         let port_info = isolation::get_allocation(&wt.branch)
             .map(|a| {
@@ -508,15 +1860,182 @@ fn cmd_status() -> Result<()> {
     Ok(())
 }
 
+// ── hooks ──────────────────────────────────────────────────────────────
+
+/// Print each hook's commands in the order they run (global, project, then
+/// worktree-local), tagged with the file that defines them.
+fn cmd_hooks_list() -> Result<()> {
+    let root = git::repo_root()?;
+    let here = git::toplevel().unwrap_or_else(|_| root.clone());
+    let layers = config::layers(&root, &here);
+    if layers.is_empty() {
+        println!("no config files found (global, {}, or .workz.local.toml)", root.join(".workz.toml").display());
+        return Ok(());
+    }
+
+    // Where each of `HooksConfig::all()` runs, in the same order
+    let runs_in = [
+        "in the main checkout, before the worktree is created",
+        "in the worktree, after every sync (start, sync, adopt)",
+        "in the new worktree, after sync",
+        "in your shell after `switch` cds; needs shell integration",
+        "in the worktree, before it is removed",
+        "in the main checkout, after removal",
+    ];
+
+    // Each command of one hook across the layers, with the file defining it.
+    // A worktree-local hook replaces the others when `local_replaces`
+    let sources = |pick: &dyn Fn(&config::Config) -> Option<config::Hook>, local_replaces: bool| {
+        let mut commands = Vec::new();
+        for layer in &layers {
+            let hook = pick(&layer.config);
+            if local_replaces && hook.is_some() && layer.path.ends_with(".workz.local.toml") {
+                commands.clear();
+            }
+            for cmd in hook.iter().flat_map(config::Hook::commands) {
+                commands.push((cmd.clone(), layer.path.display().to_string()));
+            }
+        }
+        commands
+    };
+    let print = |name: &str, runs: &str, commands: Vec<(String, String)>| {
+        println!("{}  ({})", name, runs);
+        if commands.is_empty() {
+            println!("  (none)");
+        }
+        for (cmd, source) in commands {
+            println!("  {}    # {}", cmd, source);
+        }
+    };
+
+    let defaults = config::Config::default();
+    for (i, (name, _)) in defaults.hooks.all().into_iter().enumerate() {
+        print(name, runs_in[i], sources(&|c| c.hooks.all()[i].1.clone(), true));
+    }
+    for (i, (kind, _)) in defaults.bootstrap.all().into_iter().enumerate() {
+        let commands = sources(&|c| c.bootstrap.all()[i].1.clone(), false);
+        if !commands.is_empty() {
+            print(&format!("bootstrap.{}", kind), "in the worktree on sync, before installs", commands);
+        }
+    }
+    Ok(())
+}
+
+// ── stats ──────────────────────────────────────────────────────────────
+
+/// How many shared directories `stats` lists by size.
+const STATS_TOP_SHARED: usize = 5;
+
+fn cmd_stats() -> Result<()> {
+    let worktrees: Vec<_> = git::worktree_list()?.into_iter().filter(|w| !w.is_bare).collect();
+    if worktrees.is_empty() {
+        println!("no worktrees found");
+        return Ok(());
+    }
+
+    let roots: Vec<_> = worktrees.iter().filter_map(|w| w.path.canonicalize().ok()).collect();
+    let mut used = 0u64;
+    let mut links = Vec::new();
+    for root in &roots {
+        used += disk_usage(root, &roots, &mut links);
+    }
+
+    // A symlink only saves space if it points into another worktree, i.e. it
+    // is one workz made (or could have) rather than a link the repo ships
+    let mut shared: std::collections::BTreeMap<std::path::PathBuf, usize> = Default::default();
+    for (link, target) in &links {
+        let owner = roots.iter().filter(|r| target.starts_with(r)).max_by_key(|r| r.as_os_str().len());
+        if owner.is_some_and(|o| !link.starts_with(o)) && target.is_dir() {
+            *shared.entry(target.clone()).or_default() += 1;
+        }
+    }
+    let mut shared: Vec<_> = shared
+        .into_iter()
+        .map(|(dir, count)| {
+            let size = disk_usage(&dir, &[], &mut Vec::new());
+            (dir, count, size)
+        })
+        .collect();
+    shared.sort_by_key(|(_, count, size)| std::cmp::Reverse(size * *count as u64));
+    let saved: u64 = shared.iter().map(|(_, count, size)| size * *count as u64).sum();
+    let link_count: usize = shared.iter().map(|(_, count, _)| count).sum();
+
+    println!("  worktrees   {}", worktrees.len());
+    println!("  disk used   {}", human_size(used));
+    println!(
+        "  saved       {}  ({} symlink{} to {} shared dir{})",
+        human_size(saved),
+        link_count,
+        if link_count == 1 { "" } else { "s" },
+        shared.len(),
+        if shared.len() == 1 { "" } else { "s" },
+    );
+    if !shared.is_empty() {
+        println!();
+        println!("  largest shared:");
+        for (dir, count, size) in shared.iter().take(STATS_TOP_SHARED) {
+            let shown = worktrees
+                .iter()
+                .filter_map(|w| dir.strip_prefix(w.path.canonicalize().ok()?).ok())
+                .min_by_key(|rel| rel.as_os_str().len())
+                .map(|rel| rel.display().to_string())
+                .unwrap_or_else(|| dir.display().to_string());
+            println!("    {:<32} {:>8}  ×{}", shown, human_size(*size), count);
+        }
+    }
+
+    Ok(())
+}
+
+/// Bytes of real files under `path`, without following symlinks. Symlinks met
+/// on the way are collected as (link, resolved target); directories in `skip`
+/// (other worktrees nested inside this one) are left to be counted on their own.
+fn disk_usage(
+    path: &std::path::Path,
+    skip: &[std::path::PathBuf],
+    links: &mut Vec<(std::path::PathBuf, std::path::PathBuf)>,
+) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else { continue };
+        let entry_path = entry.path();
+        if file_type.is_symlink() {
+            if let Ok(target) = entry_path.canonicalize() {
+                links.push((entry_path, target));
+            }
+        } else if file_type.is_dir() {
+            if !skip.contains(&entry_path) {
+                total += disk_usage(&entry_path, skip, links);
+            }
+        } else {
+            total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    total
+}
+
 // ── clean ──────────────────────────────────────────────────────────────
 
-fn cmd_clean(merged: bool, base: Option<&str>) -> Result<()> {
-    println!("pruning stale worktrees...");
-    let output = git::worktree_prune()?;
-    if output.is_empty() {
+fn cmd_clean(merged: bool, base: Option<&str>, dry_run: bool) -> Result<()> {
+    let worktrees = git::worktree_list()?;
+    let stale: Vec<_> = worktrees.iter().filter(|wt| wt.prunable.is_some()).collect();
+
+    println!("{} stale worktrees...", if dry_run { "would prune" } else { "pruning" });
+    for wt in &stale {
+        let reason = wt.prunable.as_deref().filter(|r| !r.is_empty()).unwrap_or("stale");
+        println!("  {} ({}): {}", wt.branch, wt.path.display(), reason);
+    }
+    if !dry_run {
+        let output = git::worktree_prune()?;
+        if !output.is_empty() {
+            println!("{}", output);
+        }
+    }
+    if stale.is_empty() {
         println!("  nothing stale to prune");
-    } else {
-        println!("{}", output);
     }
 
     if merged {
@@ -525,17 +2044,20 @@ fn cmd_clean(merged: bool, base: Option<&str>) -> Result<()> {
             .unwrap_or_else(git::default_branch);
 
         let merged_branches = git::merged_branches(&base_branch)?;
-        let worktrees = git::worktree_list()?;
 
         let to_remove: Vec<_> = worktrees
             .iter()
-            .filter(|wt| !wt.is_bare && merged_branches.contains(&wt.branch))
+            .filter(|wt| !wt.is_bare && wt.prunable.is_none() && merged_branches.contains(&wt.branch))
             .collect();
 
         if to_remove.is_empty() {
             println!("  no worktrees with merged branches found");
         } else {
             for wt in to_remove {
+                if dry_run {
+                    println!("  would remove merged worktree: {} ({})", wt.branch, wt.path.display());
+                    continue;
+                }
                 println!("  removing merged worktree: {} ({})", wt.branch, wt.path.display());
                 if let Err(e) = git::worktree_remove(&wt.path, false) {
                     eprintln!("  warning: could not remove {}: {}", wt.branch, e);
@@ -544,36 +2066,227 @@ fn cmd_clean(merged: bool, base: Option<&str>) -> Result<()> {
         }
     }
 
+    if dry_run {
+        println!("dry run — nothing was changed");
+    } else {
+        println!("done!");
+    }
+    Ok(())
+}
+
+// ── top ────────────────────────────────────────────────────────────────
+
+fn cmd_top(interval: u64) -> Result<()> {
+    use crossterm::{cursor, execute, terminal};
+    use std::io::Write;
+
+    // The shell wrapper captures stdout until workz exits; draw on the
+    // terminal directly, as `list --watch` does
+    let mut out: Box<dyn Write> = match std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(tty) => Box::new(tty),
+        Err(_) => Box::new(std::io::stdout()),
+    };
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = top_loop(&mut out, std::time::Duration::from_secs(interval));
+
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn top_loop(out: &mut impl std::io::Write, interval: std::time::Duration) -> Result<()> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use crossterm::{cursor, execute, terminal};
+
+    loop {
+        let lines = top_snapshot(interval)?;
+        execute!(out, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All))?;
+        for line in &lines {
+            // Raw mode: no implicit carriage return
+            write!(out, "{}\r\n", line)?;
+        }
+        out.flush()?;
+
+        let deadline = std::time::Instant::now() + interval;
+        while let Some(left) = deadline.checked_duration_since(std::time::Instant::now()) {
+            if !event::poll(left)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                let quit = key.code == KeyCode::Char('q')
+                    || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+                if key.kind == KeyEventKind::Press && quit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// One screenful of `top`: a row per worktree with dirty state,
+/// ahead/behind upstream, and whether its compose stack is running.
+fn top_snapshot(interval: std::time::Duration) -> Result<Vec<String>> {
+    let worktrees = visible_worktrees(false)?;
+    let width = worktrees.iter().map(|w| w.branch.len()).max().unwrap_or(6).max(6);
+
+    let mut lines = vec![
+        format!("workz top — every {}s — q to quit", interval.as_secs()),
+        String::new(),
+        format!("  {:<width$}  {:<8}  {:<9}  {:<7}  LAST COMMIT", "BRANCH", "STATE", "UPSTREAM", "DOCKER"),
+    ];
+
+    for wt in worktrees.iter().filter(|w| !w.is_bare) {
+        let state = if git::is_dirty(&wt.path).unwrap_or(false) { "modified" } else { "clean" };
+        let upstream = match git::ahead_behind(&wt.path, "@{upstream}") {
+            Some((0, 0)) => "in sync".to_string(),
+            Some((ahead, behind)) => format!("↑{} ↓{}", ahead, behind),
+            None => "-".to_string(),
+        };
+        let docker = match docker_running(&wt.path) {
+            Some(true) => "up",
+            Some(false) => "down",
+            None => "-",
+        };
+        let last = git::last_commit_relative(&wt.path).unwrap_or_default();
+        lines.push(format!(
+            "  {:<width$}  {:<8}  {:<9}  {:<7}  {}",
+            wt.branch, state, upstream, docker, last
+        ));
+    }
+
+    Ok(lines)
+}
+
+/// Whether the worktree's compose stack has running containers.
+/// None when there is no compose file or no compose tool.
+fn docker_running(path: &std::path::Path) -> Option<bool> {
+    if !has_compose_file(path) {
+        return None;
+    }
+    let output = if which_exists("podman-compose") {
+        Command::new("podman-compose").args(["ps", "-q"]).current_dir(path).output()
+    } else if which_exists("docker") {
+        Command::new("docker").args(["compose", "ps", "-q"]).current_dir(path).output()
+    } else {
+        return None;
+    };
+    let output = output.ok()?;
+    Some(output.status.success() && !String::from_utf8_lossy(&output.stdout).trim().is_empty())
+}
+
+// ── fetch ──────────────────────────────────────────────────────────────
+
+fn cmd_fetch(remote: Option<&str>, prune: bool) -> Result<()> {
+    match remote {
+        Some(r) => println!("fetching {}...", r),
+        None => println!("fetching all remotes..."),
+    }
+
+    let updates = git::fetch(remote, prune)?;
+    if updates.is_empty() {
+        println!("  already up to date");
+    } else {
+        for line in &updates {
+            println!("  {}", line);
+        }
+    }
+
     println!("done!");
     Ok(())
 }
 
+// ── version ────────────────────────────────────────────────────────────
+
+const RELEASES_API: &str = "https://api.github.com/repos/rohansx/workz/releases/latest";
+
+fn cmd_version(check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    println!("workz {}", current);
+    if !check {
+        return Ok(());
+    }
+
+    // Shell out to curl rather than pull an HTTP client into the binary;
+    // the hard timeout keeps this from ever hanging offline.
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "3", "-H", "Accept: application/vnd.github+json", RELEASES_API])
+        .output();
+    let body = match output {
+        Ok(o) if o.status.success() => o.stdout,
+        _ => bail!("could not reach GitHub to check for updates"),
+    };
+    let release: serde_json::Value =
+        serde_json::from_slice(&body).context("unexpected response from GitHub")?;
+    let Some(latest) = release["tag_name"].as_str() else {
+        bail!("unexpected response from GitHub");
+    };
+
+    let latest = latest.trim_start_matches('v');
+    if parse_version(latest) > parse_version(current) {
+        println!("update available: {} → {} (https://github.com/rohansx/workz/releases)", current, latest);
+    } else {
+        println!("up to date");
+    }
+    Ok(())
+}
+
+/// `1.2.3` → `[1, 2, 3]`; pre-release suffixes and junk count as 0.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().unwrap_or(0)
+        })
+        .collect()
+}
+
 // ── init ───────────────────────────────────────────────────────────────
 
-fn cmd_init(shell: &Shell) -> Result<()> {
-    let script = match shell {
-        Shell::Zsh | Shell::Bash => SHELL_INIT_BASH,
-        Shell::Fish => SHELL_INIT_FISH,
+/// Print the shell integration: the cd wrapper and completions, or just one
+/// of them for setups that load completions through a plugin manager.
+fn cmd_init(shell: &Shell, completions_only: bool, wrapper_only: bool) -> Result<()> {
+    let (header, wrapper, completions) = match shell {
+        Shell::Zsh | Shell::Bash => (SHELL_INIT_HEADER_BASH, SHELL_WRAPPER_BASH, SHELL_COMPLETIONS_BASH),
+        Shell::Fish => (SHELL_INIT_HEADER_FISH, SHELL_WRAPPER_FISH, SHELL_COMPLETIONS_FISH),
     };
-    print!("{}", script);
+    if completions_only {
+        print!("{}", completions);
+    } else if wrapper_only {
+        print!("{}", wrapper);
+    } else {
+        print!("{}{}\n{}", header, wrapper, completions);
+    }
     Ok(())
 }
 
-const SHELL_INIT_BASH: &str = r#"# workz shell integration
+const SHELL_INIT_HEADER_BASH: &str = r#"# workz shell integration
 # Add to your .bashrc or .zshrc:
 #   eval "$(workz init zsh)"
 
-workz() {
-    local result
-    result=$(command workz "$@" 2>&1)
+"#;
+
+const SHELL_WRAPPER_BASH: &str = r#"workz() {
+    local result tty=""
+    # Output is captured below; tell workz whether it will end up on a terminal
+    [ -t 1 ] && tty=1
+    # Fresh per call: only __workz_run: lines carrying it are eval'd, so output
+    # from anything else in the capture can't inject commands into this shell
+    local token="${RANDOM}${RANDOM}${RANDOM}${RANDOM}"
+    result=$(WORKZ_TTY=$tty WORKZ_RUN_TOKEN=$token command workz "$@" 2>&1)
     local exit_code=$?
 
     local has_cd=false
     local cd_target=""
+    local -a run_cmds=()
     while IFS= read -r line; do
         if [[ "$line" == __workz_cd:* ]]; then
             has_cd=true
             cd_target="${line#__workz_cd:}"
+        elif [[ "$line" == "__workz_run:${token}:"* ]]; then
+            run_cmds+=("${line#__workz_run:${token}:}")
         else
             printf '%s\n' "$line"
         fi
@@ -583,10 +2296,17 @@ workz() {
         builtin cd "$cd_target" || return
     fi
 
+    # post_switch hooks from your workz config, run in this shell
+    local cmd
+    for cmd in "${run_cmds[@]}"; do
+        eval "$cmd"
+    done
+
     return $exit_code
 }
+"#;
 
-# Tab completions
+const SHELL_COMPLETIONS_BASH: &str = r#"# Tab completions
 _workz_branches() {
     git worktree list --porcelain 2>/dev/null | grep '^branch ' | sed 's|^branch refs/heads/||'
 }
@@ -596,14 +2316,27 @@ if [ -n "$ZSH_VERSION" ]; then
         local -a commands
         commands=(
             'start:Create a new worktree'
+            'new:Create a worktree and print its path (no cd)'
             'list:List all worktrees'
             'ls:List all worktrees'
             'switch:Fuzzy-switch to a worktree'
             's:Fuzzy-switch to a worktree'
+            'recent:List worktrees by how often and recently you visited them'
+            'which:Print the worktree path for a branch'
+            'owners:Show which worktrees modify a file'
+            'diff:Show how two worktrees differ'
+            'move:Move a worktree to another directory'
+            'lock:Lock a worktree (e.g. on a removable drive)'
+            'unlock:Unlock a worktree'
             'sync:Sync symlinks, env files, and deps'
+            'adopt:Bring an existing git worktree under workz'
             'status:Show rich status of all worktrees'
+            'stats:Report disk used and saved by symlinks'
+            'top:Live view of worktree activity'
             'done:Remove a worktree'
             'clean:Prune orphaned worktrees'
+            'fetch:Fetch remotes and prune deleted branches'
+            'version:Print the workz version'
             'init:Print shell integration script'
         )
 
@@ -617,31 +2350,66 @@ if [ -n "$ZSH_VERSION" ]; then
                 local -a branches
                 branches=(${(f)"$(_workz_branches)"})
                 _describe 'worktree' branches
+                _arguments \
+                    '--create[Create the worktree if it does not exist]' \
+                    '--height[Picker height in lines or percent]:height:' \
+                    '--preview[Preview command for the highlighted worktree]:command:' \
+                    '--exact[Match the query exactly]' \
+                    '--regex[Treat the query as a regular expression]' \
+                    '--no-tui[Pick the best match without the interactive picker]' \
+                    '--multi[Pick several worktrees and open each in an editor]' \
+                    '--editor[Editor for --multi]:editor:(code cursor windsurf)'
                 ;;
-            done)
+            done|which|diff|move|lock|unlock)
                 local -a branches
                 branches=(${(f)"$(_workz_branches)"})
                 compadd -- "${branches[@]}"
                 ;;
-            start)
+            start|new)
                 _arguments \
                     '1:branch:' \
                     '--base[Base branch]:branch:' \
                     '-b[Base branch]:branch:' \
+                    '--base-remote[Branch from the fetched remote default branch]' \
                     '--no-sync[Skip sync operations]' \
                     '--ai[Launch AI coding tool]' \
                     '-a[Launch AI coding tool]' \
                     '--ai-tool[AI tool]:tool:(claude cursor code)' \
                     '--docker[Run docker compose up]' \
-                    '--isolated[Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME]'
+                    '--docker-build[Rebuild images and run docker compose up]' \
+                    '--isolated[Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME]' \
+                    '--copy-untracked[Copy untracked files from the current worktree]' \
+                    '--clone-ignored[Clone git-ignored files from the current worktree]' \
+                    '--submodules[Initialize submodules in the new worktree]' \
+                    '--name[Directory suffix instead of the branch name]:name:' \
+                    '--shell[Open a subshell in the new worktree]' \
+                    '--install-cmd[Run this instead of the detected install command]:command:' \
+                    '--from[Branch off the HEAD of another worktree]:worktree:' \
+                    '--jobs[Cap package manager install concurrency]:jobs:' \
+                    '--no-track[Create the branch without an upstream]' \
+                    '--from-tag[Branch off a release tag]:tag:' \
+                    '*--env[Set KEY=VAL for the post_start hook]:KEY=VAL:' \
+                    '--reuse[Delete a stray non-worktree directory at the target first]' \
+                    '--detach-editor[Fully detach the launched tool from the terminal]' \
+                    '--no-template[Use the branch name as typed, ignoring branch_template]' \
+                    '--quiet-install[Hide install output unless it fails]' \
+                    '--reset-base[Reset an existing branch to the base tip]' \
+                    '--force[Reset without asking]' \
+                    '--from-pr[Check out a GitHub pull request]:number:' \
+                    '--rollback-on-error[Remove the new worktree if setup fails]' \
+                    '--open-in-tmux[Open the worktree in its own tmux window]'
                 ;;
             clean)
                 _arguments \
                     '--merged[Remove worktrees with merged branches]' \
-                    '--base[Base branch]:branch:'
+                    '--base[Base branch]:branch:' \
+                    '--dry-run[Only list what would be cleaned]'
                 ;;
             init)
-                compadd -- zsh bash fish
+                _arguments \
+                    '1:shell:(zsh bash fish)' \
+                    '--completions-only[Print only the tab completions]' \
+                    '--wrapper-only[Print only the cd wrapper function]'
                 ;;
         esac
     }
@@ -653,7 +2421,7 @@ else
         prev="${COMP_WORDS[COMP_CWORD-1]}"
 
         if [[ ${COMP_CWORD} -eq 1 ]]; then
-            COMPREPLY=($(compgen -W "start list ls switch s sync status done clean init" -- "$cur"))
+            COMPREPLY=($(compgen -W "start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -- "$cur"))
             return
         fi
 
@@ -661,20 +2429,20 @@ else
             switch|s)
                 COMPREPLY=($(compgen -W "$(_workz_branches)" -- "$cur"))
                 ;;
-            done)
+            done|which|diff|move|lock|unlock)
                 COMPREPLY=($(compgen -W "$(_workz_branches)" -- "$cur"))
                 ;;
-            start)
-                COMPREPLY=($(compgen -W "--base --no-sync --ai --ai-tool --docker --isolated" -- "$cur"))
+            start|new)
+                COMPREPLY=($(compgen -W "--base --base-remote --no-sync --ai --ai-tool --docker --docker-build --isolated --copy-untracked --clone-ignored --submodules --name --shell --install-cmd --from --jobs --no-track --from-tag --env --reuse --detach-editor --no-template --quiet-install --reset-base --force --from-pr --rollback-on-error --open-in-tmux" -- "$cur"))
                 if [[ "$prev" == "--ai-tool" ]]; then
                     COMPREPLY=($(compgen -W "claude cursor code aider codex gemini windsurf" -- "$cur"))
                 fi
                 ;;
             clean)
-                COMPREPLY=($(compgen -W "--merged --base" -- "$cur"))
+                COMPREPLY=($(compgen -W "--merged --base --dry-run" -- "$cur"))
                 ;;
             init)
-                COMPREPLY=($(compgen -W "zsh bash fish" -- "$cur"))
+                COMPREPLY=($(compgen -W "zsh bash fish --completions-only --wrapper-only" -- "$cur"))
                 ;;
         esac
     }
@@ -682,46 +2450,114 @@ else
 fi
 "#;
 
-const SHELL_INIT_FISH: &str = r#"# workz shell integration
+const SHELL_INIT_HEADER_FISH: &str = r#"# workz shell integration
 # Add to your config.fish:
 #   workz init fish | source
 
-function workz
-    set -l result (command workz $argv 2>&1)
+"#;
+
+const SHELL_WRAPPER_FISH: &str = r#"function workz
+    # Output is captured below; tell workz whether it will end up on a terminal
+    set -l tty ""
+    isatty stdout; and set tty 1
+    # Fresh per call: only __workz_run: lines carrying it are eval'd, so output
+    # from anything else in the capture can't inject commands into this shell
+    set -l token (random)(random)(random)(random)
+    set -l result (env WORKZ_TTY=$tty WORKZ_RUN_TOKEN=$token workz $argv 2>&1)
     set -l exit_code $status
 
+    set -l run_cmds
     for line in $result
         if string match -q '__workz_cd:*' $line
             set -l target (string replace '__workz_cd:' '' $line)
             builtin cd $target
+        else if string match -q -- "__workz_run:$token:*" $line
+            set -a run_cmds (string replace -- "__workz_run:$token:" '' $line)
         else
             echo $line
         end
     end
 
+    # post_switch hooks from your workz config, run in this shell
+    for cmd in $run_cmds
+        eval $cmd
+    end
+
     return $exit_code
 end
+"#;
 
-# Tab completions
+const SHELL_COMPLETIONS_FISH: &str = r#"# Tab completions
 complete -c workz -e
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a start -d "Create a new worktree"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a list -d "List all worktrees"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a switch -d "Fuzzy-switch to a worktree"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a sync -d "Sync symlinks, env files, and deps"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a status -d "Show rich status of all worktrees"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a done -d "Remove a worktree"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a clean -d "Prune orphaned worktrees"
-complete -c workz -n "not __fish_seen_subcommand_from start list ls switch s sync status done clean init" -a init -d "Print shell integration script"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a start -d "Create a new worktree"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a new -d "Create a worktree and print its path"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a list -d "List all worktrees"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a switch -d "Fuzzy-switch to a worktree"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a recent -d "List worktrees by frecency"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a which -d "Print the worktree path for a branch"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a owners -d "Show which worktrees modify a file"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a diff -d "Show how two worktrees differ"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a move -d "Move a worktree to another directory"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a lock -d "Lock a worktree"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a unlock -d "Unlock a worktree"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a sync -d "Sync symlinks, env files, and deps"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a adopt -d "Bring an existing git worktree under workz"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a status -d "Show rich status of all worktrees"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a stats -d "Report disk used and saved by symlinks"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a top -d "Live view of worktree activity"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a done -d "Remove a worktree"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a clean -d "Prune orphaned worktrees"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a fetch -d "Fetch remotes and prune deleted branches"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a version -d "Print the workz version"
+complete -c workz -n "not __fish_seen_subcommand_from start new list ls switch s recent which owners diff move lock unlock sync adopt status stats top done clean fetch version init" -a init -d "Print shell integration script"
 complete -c workz -n "__fish_seen_subcommand_from switch s" -a "(git worktree list --porcelain 2>/dev/null | string match -r '^branch refs/heads/(.+)' | string replace 'branch refs/heads/' '')"
-complete -c workz -n "__fish_seen_subcommand_from done" -a "(git worktree list --porcelain 2>/dev/null | string match -r '^branch refs/heads/(.+)' | string replace 'branch refs/heads/' '')"
-complete -c workz -n "__fish_seen_subcommand_from start" -l base -d "Base branch"
-complete -c workz -n "__fish_seen_subcommand_from start" -l no-sync -d "Skip sync operations"
-complete -c workz -n "__fish_seen_subcommand_from start" -s a -l ai -d "Launch AI coding tool"
-complete -c workz -n "__fish_seen_subcommand_from start" -l ai-tool -a "claude cursor code aider codex gemini windsurf" -d "AI tool to launch"
-complete -c workz -n "__fish_seen_subcommand_from start" -l docker -d "Run docker compose up"
-complete -c workz -n "__fish_seen_subcommand_from start" -l isolated -d "Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME"
+complete -c workz -n "__fish_seen_subcommand_from done which diff move lock unlock" -a "(git worktree list --porcelain 2>/dev/null | string match -r '^branch refs/heads/(.+)' | string replace 'branch refs/heads/' '')"
+complete -c workz -n "__fish_seen_subcommand_from list ls" -l bars -d "Show size bars and color by commit age"
+complete -c workz -n "__fish_seen_subcommand_from list ls" -s w -l watch -d "Reprint when worktree state changes"
+complete -c workz -n "__fish_seen_subcommand_from list ls" -l by-project-type -d "Group by detected project type"
+complete -c workz -n "__fish_seen_subcommand_from list ls" -l no-size -d "Skip size computation"
+complete -c workz -n "__fish_seen_subcommand_from list ls" -l sizes -d "Measure full recursive sizes"
+complete -c workz -n "__fish_seen_subcommand_from switch s" -s c -l create -d "Create the worktree if it does not exist"
+complete -c workz -n "__fish_seen_subcommand_from switch s" -l multi -d "Pick several worktrees and open each in an editor"
+complete -c workz -n "__fish_seen_subcommand_from switch s" -l editor -a "code cursor windsurf" -d "Editor for --multi"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l base -d "Base branch"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l no-sync -d "Skip sync operations"
+complete -c workz -n "__fish_seen_subcommand_from start new" -s a -l ai -d "Launch AI coding tool"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l ai-tool -a "claude cursor code aider codex gemini windsurf" -d "AI tool to launch"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l docker -d "Run docker compose up"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l docker-build -d "Rebuild images and run docker compose up"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l isolated -d "Auto-assign PORT, DB_NAME, COMPOSE_PROJECT_NAME"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l copy-untracked -d "Copy untracked files from the current worktree"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l clone-ignored -d "Clone git-ignored files from the current worktree"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l base-remote -d "Branch from the fetched remote default branch"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l shell -d "Open a subshell in the new worktree"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l name -d "Directory suffix instead of the branch name"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l submodules -d "Initialize submodules in the new worktree"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l install-cmd -r -d "Run this instead of the detected install command"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l from -x -d "Branch off the HEAD of another worktree"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l jobs -x -d "Cap package manager install concurrency"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l no-track -d "Create the branch without an upstream"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l from-tag -x -d "Branch off a release tag"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l env -x -d "Set KEY=VAL for the post_start hook"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l reuse -d "Delete a stray non-worktree directory at the target first"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l detach-editor -d "Fully detach the launched tool from the terminal"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l no-template -d "Use the branch name as typed, ignoring branch_template"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l quiet-install -d "Hide install output unless it fails"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l reset-base -d "Reset an existing branch to the base tip"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l force -d "Reset without asking"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l from-pr -x -d "Check out a GitHub pull request"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l rollback-on-error -d "Remove the new worktree if setup fails"
+complete -c workz -n "__fish_seen_subcommand_from start new" -l open-in-tmux -d "Open the worktree in its own tmux window"
+complete -c workz -n "__fish_seen_subcommand_from diff" -l working -d "Compare working trees, uncommitted changes included"
 complete -c workz -n "__fish_seen_subcommand_from done" -l cleanup-db -d "Drop the database created by --isolated"
+complete -c workz -n "__fish_seen_subcommand_from done" -l prune-remote -d "Also delete the branch on its upstream remote"
+complete -c workz -n "__fish_seen_subcommand_from done" -l all -d "Remove every clean, merged, pushed worktree"
+complete -c workz -n "__fish_seen_subcommand_from done" -l parallel -d "Remove up to N worktrees at once (with --all)"
+complete -c workz -n "__fish_seen_subcommand_from done" -s y -l yes -d "Don't ask for confirmation"
 complete -c workz -n "__fish_seen_subcommand_from clean" -l merged -d "Remove worktrees with merged branches"
 complete -c workz -n "__fish_seen_subcommand_from clean" -l base -d "Base branch to check merged against"
+complete -c workz -n "__fish_seen_subcommand_from clean" -l dry-run -d "Only list what would be cleaned"
 complete -c workz -n "__fish_seen_subcommand_from init" -a "zsh bash fish"
+complete -c workz -n "__fish_seen_subcommand_from init" -l completions-only -d "Print only the tab completions"
+complete -c workz -n "__fish_seen_subcommand_from init" -l wrapper-only -d "Print only the cd wrapper function"
 "#;
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+use crate::config::MatchMode;
+
+/// Rank `candidates` against `query` without a terminal UI — what `switch`
+/// falls back to when skim can't run. Returns indexes into `candidates`, best
+/// first; non-matches are dropped. Exact and regex modes keep the input order.
+pub fn rank(candidates: &[String], query: &str, mode: MatchMode) -> Result<Vec<usize>> {
+    match mode {
+        MatchMode::Fuzzy => {
+            let matcher = SkimMatcherV2::default().smart_case();
+            let mut scored: Vec<(i64, usize)> = candidates
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| Some((matcher.fuzzy_match(c, query)?, i)))
+                .collect();
+            // Stable sort: equal scores keep worktree order
+            scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+            Ok(scored.into_iter().map(|(_, i)| i).collect())
+        }
+        MatchMode::Exact => {
+            let query = query.to_lowercase();
+            Ok((0..candidates.len()).filter(|&i| candidates[i].to_lowercase().contains(&query)).collect())
+        }
+        MatchMode::Regex => {
+            let re = regex::Regex::new(query).with_context(|| format!("invalid regex '{}'", query))?;
+            Ok((0..candidates.len()).filter(|&i| re.is_match(&candidates[i])).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branches(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn fuzzy_ranks_tighter_matches_first() {
+        let c = branches(&["feature/billing-api", "fix/login", "feature/login-page"]);
+        let ranked = rank(&c, "login", MatchMode::Fuzzy).unwrap();
+        assert_eq!(ranked.len(), 2);
+        assert!(!ranked.contains(&0));
+        assert!(rank(&c, "zzz", MatchMode::Fuzzy).unwrap().is_empty());
+    }
+
+    #[test]
+    fn exact_and_regex_modes() {
+        let c = branches(&["feat/api", "fix/a-p-i", "main"]);
+        assert_eq!(rank(&c, "API", MatchMode::Exact).unwrap(), [0]);
+        assert_eq!(rank(&c, "^f.*i$", MatchMode::Regex).unwrap(), [0, 1]);
+        assert!(rank(&c, "(", MatchMode::Regex).is_err());
+    }
+}
@@ -6,10 +6,12 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, Read, Write};
 use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
 
-use crate::{config, git, isolation, sync};
+use crate::{config, git, hooks, isolation, sync};
 
 // ── JSON-RPC types ──────────────────────────────────────────────────────
 
@@ -39,6 +41,15 @@ struct RpcError {
     message: String,
 }
 
+/// What goes back for one incoming message: a single response, or an array
+/// of them for a JSON-RPC batch.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Reply {
+    One(Response),
+    Batch(Vec<Response>),
+}
+
 impl Response {
     fn ok(id: Value, result: Value) -> Self {
         Self { jsonrpc: "2.0".into(), id, result: Some(result), error: None }
@@ -55,39 +66,229 @@ impl Response {
 
 // ── Server loop ─────────────────────────────────────────────────────────
 
-pub fn run() -> Result<()> {
-    let stdin = io::stdin();
+/// Default cap on a single incoming message (4 MiB).
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// How messages are delimited on stdio. Detected from the client's first
+/// bytes; responses are written back the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Framing {
+    /// One JSON document per line.
+    Lines,
+    /// LSP-style `Content-Length: N\r\n\r\n<json>`.
+    ContentLength,
+}
+
+/// A unit read from stdin.
+#[derive(Debug, PartialEq)]
+enum Frame {
+    Message(String),
+    /// The message exceeded the size cap and was discarded.
+    Oversized,
+}
+
+pub fn run(max_message_bytes: usize, idle_timeout: Option<Duration>) -> Result<()> {
+    let frames = spawn_reader(max_message_bytes);
     let stdout = io::stdout();
     let mut out = stdout.lock();
 
-    for line in stdin.lock().lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
+    loop {
+        let frame = match idle_timeout {
+            Some(timeout) => match frames.recv_timeout(timeout) {
+                Ok(frame) => frame,
+                Err(RecvTimeoutError::Timeout) => {
+                    eprintln!("workz mcp: no messages for {}s, exiting", timeout.as_secs());
+                    break;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+            None => match frames.recv() {
+                Ok(frame) => frame,
+                Err(_) => break, // EOF
+            },
+        };
+
+        let (framing, frame) = frame?;
+        let resp = match frame {
+            Frame::Oversized => Some(Reply::One(Response::err(
+                Value::Null,
+                -32600,
+                format!("message exceeds {max_message_bytes} bytes"),
+            ))),
+            Frame::Message(line) => handle_message(&line),
+        };
+
+        if let Some(resp) = resp {
+            match write_response(&mut out, &resp, framing) {
+                // Client went away — nothing left to serve
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+                result => result?,
+            }
         }
+    }
 
-        let req: Request = match serde_json::from_str(&line) {
-            Ok(r) => r,
+    Ok(())
+}
+
+/// Read frames on a background thread so the main loop can time out.
+/// The channel closes on EOF; a read error is forwarded once.
+fn spawn_reader(max_bytes: usize) -> Receiver<io::Result<(Framing, Frame)>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let stdin = io::stdin();
+        let mut reader = stdin.lock();
+        let framing = match detect_framing(&mut reader) {
+            Ok(framing) => framing,
             Err(e) => {
-                let resp = Response::err(Value::Null, -32700, format!("parse error: {e}"));
-                writeln!(out, "{}", serde_json::to_string(&resp)?)?;
-                out.flush()?;
-                continue;
+                let _ = tx.send(Err(e));
+                return;
             }
         };
+        loop {
+            let frame = match framing {
+                Framing::Lines => read_line_frame(&mut reader, max_bytes),
+                Framing::ContentLength => read_framed(&mut reader, max_bytes),
+            };
+            match frame {
+                Ok(Some(frame)) => {
+                    if tx.send(Ok((framing, frame))).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    break;
+                }
+            }
+        }
+    });
+    rx
+}
 
-        // Notifications have no id — don't respond
-        let id = match req.id.clone() {
-            Some(id) => id,
-            None => continue,
-        };
+/// Peek at the first byte: a JSON message starts with `{` (or whitespace),
+/// a framed one with the `Content-Length` header.
+fn detect_framing(reader: &mut impl BufRead) -> io::Result<Framing> {
+    let buf = reader.fill_buf()?;
+    Ok(match buf.first() {
+        Some(b'C') | Some(b'c') => Framing::ContentLength,
+        _ => Framing::Lines,
+    })
+}
 
-        let resp = dispatch(&req.method, id, &req.params);
-        writeln!(out, "{}", serde_json::to_string(&resp)?)?;
-        out.flush()?;
+/// Read one `Content-Length` framed message. Bodies over `max_bytes` are
+/// skipped without being buffered.
+fn read_framed(reader: &mut impl BufRead, max_bytes: usize) -> io::Result<Option<Frame>> {
+    let mut length = None;
+    let mut saw_header = false;
+    loop {
+        let mut line = String::new();
+        if reader.by_ref().take(1024).read_line(&mut line)? == 0 {
+            if saw_header {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated MCP frame header"));
+            }
+            return Ok(None);
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            if saw_header {
+                break;
+            }
+            continue; // stray blank line between frames
+        }
+        saw_header = true;
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
     }
 
-    Ok(())
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "MCP frame is missing a valid Content-Length header")
+    })?;
+    if length > max_bytes {
+        io::copy(&mut reader.by_ref().take(length as u64), &mut io::sink())?;
+        return Ok(Some(Frame::Oversized));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Frame::Message(String::from_utf8_lossy(&body).into_owned())))
+}
+
+/// Read one newline-delimited message without buffering more than `max_bytes`.
+fn read_line_frame(reader: &mut impl BufRead, max_bytes: usize) -> io::Result<Option<Frame>> {
+    let mut buf = Vec::new();
+    let n = reader.by_ref().take(max_bytes as u64 + 1).read_until(b'\n', &mut buf)?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if buf.len() > max_bytes && buf.last() != Some(&b'\n') {
+        skip_line(reader)?;
+        return Ok(Some(Frame::Oversized));
+    }
+    Ok(Some(Frame::Message(String::from_utf8_lossy(&buf).into_owned())))
+}
+
+/// Discard input up to and including the next newline.
+fn skip_line(reader: &mut impl BufRead) -> io::Result<()> {
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(());
+        }
+        if let Some(pos) = available.iter().position(|&b| b == b'\n') {
+            reader.consume(pos + 1);
+            return Ok(());
+        }
+        let len = available.len();
+        reader.consume(len);
+    }
+}
+
+/// Parse and dispatch one message. Notifications (no id) get no response.
+fn handle_message(line: &str) -> Option<Reply> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    let value: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => return Some(Reply::One(Response::err(Value::Null, -32700, format!("parse error: {e}")))),
+    };
+
+    match value {
+        Value::Array(items) if items.is_empty() => {
+            Some(Reply::One(Response::err(Value::Null, -32600, "empty batch")))
+        }
+        // A batch gets an array back; notifications in it get no entry, and a
+        // batch of only notifications gets no reply at all.
+        Value::Array(items) => {
+            let responses: Vec<Response> = items.into_iter().filter_map(handle_request).collect();
+            (!responses.is_empty()).then_some(Reply::Batch(responses))
+        }
+        value => handle_request(value).map(Reply::One),
+    }
+}
+
+fn handle_request(value: Value) -> Option<Response> {
+    let req: Request = match serde_json::from_value(value) {
+        Ok(r) => r,
+        Err(e) => return Some(Response::err(Value::Null, -32600, format!("invalid request: {e}"))),
+    };
+
+    let id = req.id.clone()?;
+    Some(dispatch(&req.method, id, &req.params))
+}
+
+fn write_response(out: &mut impl Write, resp: &Reply, framing: Framing) -> io::Result<()> {
+    let body = serde_json::to_string(resp)?;
+    match framing {
+        Framing::Lines => writeln!(out, "{body}")?,
+        Framing::ContentLength => write!(out, "Content-Length: {}\r\n\r\n{body}", body.len())?,
+    }
+    out.flush()
 }
 
 // ── Dispatch ────────────────────────────────────────────────────────────
@@ -97,7 +298,7 @@ fn dispatch(method: &str, id: Value, params: &Value) -> Response {
         "initialize" => Response::ok(
             id,
             json!({
-                "protocolVersion": "2024-11-05",
+                "protocolVersion": negotiate_version(&params["protocolVersion"]),
                 "capabilities": { "tools": {} },
                 "serverInfo": {
                     "name": "workz",
@@ -106,33 +307,69 @@ fn dispatch(method: &str, id: Value, params: &Value) -> Response {
             }),
         ),
 
-        "tools/list" => Response::ok(id, json!({ "tools": tool_definitions() })),
+        // Liveness check — an empty result means we're alive
+        "ping" => Response::ok(id, json!({})),
+
+        // Every tool fits on one page, so any `cursor` is answered with the
+        // full list and no next page
+        "tools/list" => Response::ok(id, json!({ "tools": tool_definitions(), "nextCursor": null })),
 
         "tools/call" => {
             let name = params["name"].as_str().unwrap_or("");
             let args = &params["arguments"];
-            match call_tool(name, args) {
-                Ok(text) => Response::ok(
-                    id,
-                    json!({ "content": [{ "type": "text", "text": text }] }),
-                ),
-                Err(e) => Response::ok(
-                    id,
-                    json!({
-                        "content": [{ "type": "text", "text": format!("Error: {e}") }],
-                        "isError": true
-                    }),
-                ),
-            }
+            Response::ok(id, tool_result(call_tool(name, args)))
         }
 
         _ => Response::err(id, -32601, format!("method not found: {method}")),
     }
 }
 
+/// Protocol revisions we can speak. `structuredContent` arrived in 2025-06-18;
+/// older clients just ignore the extra field.
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Answer with the client's requested revision when we know it, else the
+/// latest we support, as the spec asks; the client decides whether to go on.
+fn negotiate_version(requested: &Value) -> &'static str {
+    PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| requested.as_str() == Some(**v))
+        .unwrap_or(&PROTOCOL_VERSIONS[0])
+}
+
+/// Shape a tool's outcome as a `tools/call` result.
+fn tool_result(output: Result<ToolOutput>) -> Value {
+    match output {
+        Ok(ToolOutput { text, structured: None }) => {
+            json!({ "content": [{ "type": "text", "text": text }] })
+        }
+        Ok(ToolOutput { text, structured: Some(data) }) => json!({
+            "content": [{ "type": "text", "text": text }],
+            "structuredContent": data
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": format!("Error: {e}") }],
+            "isError": true
+        }),
+    }
+}
+
 // ── Tool implementations ────────────────────────────────────────────────
 
-fn call_tool(name: &str, args: &Value) -> Result<String> {
+/// What a tool returns: text for display, plus the same data as a JSON object
+/// for tools with an `outputSchema`.
+struct ToolOutput {
+    text: String,
+    structured: Option<Value>,
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        Self { text, structured: None }
+    }
+}
+
+fn call_tool(name: &str, args: &Value) -> Result<ToolOutput> {
     match name {
         "workz_start" => {
             let branch = args["branch"]
@@ -149,14 +386,15 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
                 return Ok(format!(
                     "worktree already exists\nbranch: {branch}\npath: {}",
                     wt_path.display()
-                ));
+                )
+                .into());
             }
 
-            let config = config::load_config(&root)?;
             git::worktree_add(&wt_path, branch, base)?;
+            let config = config::load_worktree_config(&root, &wt_path)?;
 
             let framework = if !no_sync {
-                sync::sync_worktree(&root, &wt_path, &config.sync)?
+                sync::sync_worktree(&root, &wt_path, &config)?
             } else {
                 sync::Framework::Unknown
             };
@@ -179,13 +417,29 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
                 ));
             }
 
-            Ok(result)
+            // Same hooks as `workz start`, with their output returned instead of printed
+            if !no_sync {
+                let env = hooks::env(&root, &wt_path, branch);
+                let mut transcript = hooks::run_captured("post_sync", &config.hooks.post_sync, &wt_path, &env)?;
+                transcript += &hooks::run_captured("post_start", &config.hooks.post_start, &wt_path, &env)?;
+                if !transcript.is_empty() {
+                    result.push_str(&format!("\nhooks:\n{}", transcript.trim_end()));
+                }
+            }
+
+            Ok(result.into())
         }
 
         "workz_list" => {
+            let all = args["all"].as_bool().unwrap_or(false);
+            let root = git::repo_root()?;
+            let repo_name = git::repo_name(&root);
+            let repo_root = root.to_string_lossy();
+            let config = config::load_config(&root)?;
             let worktrees = git::worktree_list()?;
             let list: Vec<Value> = worktrees
                 .iter()
+                .filter(|wt| all || !config.list.hides(&wt.path))
                 .map(|wt| {
                     let dirty = git::is_dirty(&wt.path).unwrap_or(false);
                     let last = git::last_commit_relative(&wt.path);
@@ -195,34 +449,62 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
                         "is_bare": wt.is_bare,
                         "modified": dirty,
                         "last_commit": last,
+                        "repo_name": repo_name,
+                        "repo_root": repo_root,
                     })
                 })
                 .collect();
-            Ok(serde_json::to_string_pretty(&list)?)
+            let out = json!({
+                "repo": { "name": repo_name, "root": repo_root },
+                "worktrees": list,
+            });
+            Ok(ToolOutput { text: serde_json::to_string_pretty(&out)?, structured: Some(out) })
         }
 
         "workz_status" => {
             let worktrees = git::worktree_list()?;
             let mut lines = Vec::new();
+            let mut entries = Vec::new();
             for wt in &worktrees {
                 if wt.is_bare {
                     lines.push(format!("{} {} (bare)", wt.branch, wt.path.display()));
+                    entries.push(json!({
+                        "branch": wt.branch,
+                        "path": wt.path.to_string_lossy(),
+                        "is_bare": true,
+                    }));
                     continue;
                 }
-                let dirty =
-                    if git::is_dirty(&wt.path).unwrap_or(false) { " [modified]" } else { "" };
-                let last = git::last_commit_relative(&wt.path)
-                    .map(|t| format!("  {t}"))
+                let modified = git::is_dirty(&wt.path).unwrap_or(false);
+                let last_commit = git::last_commit_relative(&wt.path);
+                let upstream = git::ahead_behind(&wt.path, "@{upstream}");
+                let dirty = if modified { " [modified]" } else { "" };
+                let ahead_behind = upstream
+                    .map(|(ahead, behind)| format!(" ↑{ahead} ↓{behind}"))
                     .unwrap_or_default();
+                let last = last_commit.as_ref().map(|t| format!("  {t}")).unwrap_or_default();
                 lines.push(format!(
-                    "{}  {}{}{}",
+                    "{}  {}{}{}{}",
                     wt.branch,
                     wt.path.display(),
                     dirty,
+                    ahead_behind,
                     last
                 ));
+                entries.push(json!({
+                    "branch": wt.branch,
+                    "path": wt.path.to_string_lossy(),
+                    "is_bare": false,
+                    "modified": modified,
+                    "ahead": upstream.map(|(ahead, _)| ahead),
+                    "behind": upstream.map(|(_, behind)| behind),
+                    "last_commit": last_commit,
+                }));
             }
-            Ok(lines.join("\n"))
+            Ok(ToolOutput {
+                text: lines.join("\n"),
+                structured: Some(json!({ "worktrees": entries })),
+            })
         }
 
         "workz_sync" => {
@@ -234,9 +516,16 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
             if path == root {
                 anyhow::bail!("cannot sync the main worktree");
             }
-            let config = config::load_config(&root)?;
-            let _framework = sync::sync_worktree(&root, &path, &config.sync)?;
-            Ok(format!("synced worktree at {}", path.display()))
+            let config = config::load_worktree_config(&root, &path)?;
+            let _framework = sync::sync_worktree(&root, &path, &config)?;
+            let branch = git::current_branch(&path).unwrap_or_default();
+            let env = hooks::env(&root, &path, &branch);
+            let transcript = hooks::run_captured("post_sync", &config.hooks.post_sync, &path, &env)?;
+            let mut result = format!("synced worktree at {}", path.display());
+            if !transcript.is_empty() {
+                result.push_str(&format!("\nhooks:\n{}", transcript.trim_end()));
+            }
+            Ok(result.into())
         }
 
         "workz_done" => {
@@ -255,7 +544,7 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
             }
 
             git::worktree_remove(&wt_path, force)?;
-            Ok(format!("removed worktree at {}", wt_path.display()))
+            Ok(format!("removed worktree at {}", wt_path.display()).into())
         }
 
         "workz_conflicts" => {
@@ -274,17 +563,24 @@ fn call_tool(name: &str, args: &Value) -> Result<String> {
 
             let mut conflicts: Vec<_> =
                 file_map.iter().filter(|(_, branches)| branches.len() > 1).collect();
-
-            if conflicts.is_empty() {
-                Ok("no conflicts detected between worktrees".to_string())
+            conflicts.sort_by_key(|(f, _)| f.as_str());
+            let structured = json!({
+                "conflicts": conflicts
+                    .iter()
+                    .map(|(file, branches)| json!({ "file": file, "branches": branches }))
+                    .collect::<Vec<_>>()
+            });
+
+            let text = if conflicts.is_empty() {
+                "no conflicts detected between worktrees".to_string()
             } else {
-                conflicts.sort_by_key(|(f, _)| f.as_str());
                 let mut out = String::from("conflicting files:\n");
                 for (file, branches) in &conflicts {
                     out.push_str(&format!("  {} — modified in: {}\n", file, branches.join(", ")));
                 }
-                Ok(out)
-            }
+                out
+            };
+            Ok(ToolOutput { text, structured: Some(structured) })
         }
 
         _ => anyhow::bail!("unknown tool: {name}"),
@@ -297,7 +593,7 @@ fn tool_definitions() -> Value {
     json!([
         {
             "name": "workz_start",
-            "description": "Create a new git worktree with auto-synced dependencies, env files, and IDE configs. Returns the worktree path so you can work in it.",
+            "description": "Create a new git worktree with auto-synced dependencies, env files, and IDE configs, then run the post_sync and post_start hooks. Returns the worktree path (and any hook output) so you can work in it.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -311,17 +607,35 @@ fn tool_definitions() -> Value {
         },
         {
             "name": "workz_list",
-            "description": "List all git worktrees with branch name, path, modified status, and last commit time.",
-            "inputSchema": { "type": "object", "properties": {} }
+            "description": "List all git worktrees of the current repo (identified by a top-level repo name and root) with branch name, path, modified status, and last commit time. Worktrees containing a .workzignore marker are hidden unless all is true.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "all": { "type": "boolean", "description": "Include worktrees hidden by a .workzignore marker" }
+                }
+            },
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": { "type": "object", "properties": { "name": { "type": "string" }, "root": { "type": "string" } } },
+                    "worktrees": { "type": "array", "items": { "type": "object" } }
+                },
+                "required": ["repo", "worktrees"]
+            }
         },
         {
             "name": "workz_status",
-            "description": "Show rich status of all worktrees: branch, path, uncommitted changes, last commit age.",
-            "inputSchema": { "type": "object", "properties": {} }
+            "description": "Show rich status of all worktrees: branch, path, uncommitted changes, commits ahead/behind upstream (null when the branch has no upstream, so ahead > 0 means unpushed work), last commit age.",
+            "inputSchema": { "type": "object", "properties": {} },
+            "outputSchema": {
+                "type": "object",
+                "properties": { "worktrees": { "type": "array", "items": { "type": "object" } } },
+                "required": ["worktrees"]
+            }
         },
         {
             "name": "workz_sync",
-            "description": "Re-sync symlinks, env files, and dependencies into a worktree. Useful for worktrees not created by workz.",
+            "description": "Re-sync symlinks, env files, and dependencies into a worktree, then run the post_sync hook. Useful for worktrees not created by workz.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -343,7 +657,110 @@ fn tool_definitions() -> Value {
         {
             "name": "workz_conflicts",
             "description": "Detect files modified in multiple worktrees simultaneously — potential merge conflicts before they happen.",
-            "inputSchema": { "type": "object", "properties": {} }
+            "inputSchema": { "type": "object", "properties": {} },
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "conflicts": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "file": { "type": "string" },
+                                "branches": { "type": "array", "items": { "type": "string" } }
+                            }
+                        }
+                    }
+                },
+                "required": ["conflicts"]
+            }
         }
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn message(frame: Option<Frame>) -> String {
+        match frame {
+            Some(Frame::Message(body)) => body,
+            other => panic!("expected a message, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn detects_framing_from_first_byte() {
+        let mut framed = Cursor::new(b"Content-Length: 2\r\n\r\n{}".to_vec());
+        assert_eq!(detect_framing(&mut framed).unwrap(), Framing::ContentLength);
+
+        let mut lines = Cursor::new(b"{\"jsonrpc\":\"2.0\"}\n".to_vec());
+        assert_eq!(detect_framing(&mut lines).unwrap(), Framing::Lines);
+    }
+
+    #[test]
+    fn reads_consecutive_framed_messages() {
+        let input = b"Content-Length: 7\r\n\r\n{\"a\":1}content-length: 2\r\nContent-Type: application/json\r\n\r\n{}";
+        let mut reader = Cursor::new(input.to_vec());
+
+        assert_eq!(message(read_framed(&mut reader, 1024).unwrap()), r#"{"a":1}"#);
+        assert_eq!(message(read_framed(&mut reader, 1024).unwrap()), "{}");
+        assert!(read_framed(&mut reader, 1024).unwrap().is_none());
+    }
+
+    #[test]
+    fn skips_oversized_framed_body() {
+        let input = b"Content-Length: 10\r\n\r\n0123456789Content-Length: 2\r\n\r\n{}";
+        let mut reader = Cursor::new(input.to_vec());
+
+        assert_eq!(read_framed(&mut reader, 5).unwrap(), Some(Frame::Oversized));
+        assert_eq!(message(read_framed(&mut reader, 5).unwrap()), "{}");
+    }
+
+    #[test]
+    fn ping_returns_empty_result() {
+        let resp = serde_json::to_value(dispatch("ping", json!(7), &Value::Null)).unwrap();
+        assert_eq!(resp["id"], 7);
+        assert_eq!(resp["result"], json!({}));
+        assert!(resp.get("error").is_none());
+    }
+
+    #[test]
+    fn batch_gets_array_without_notification_replies() {
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"ping"},
+            {"jsonrpc":"2.0","method":"notifications/initialized"},
+            {"jsonrpc":"2.0","id":2,"method":"nope"}
+        ]"#;
+        let reply = serde_json::to_value(handle_message(batch)).unwrap();
+        let responses = reply.as_array().expect("batch reply is an array");
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], json!({}));
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[1]["error"].is_object());
+
+        let only_notifications = r#"[{"jsonrpc":"2.0","method":"notifications/initialized"}]"#;
+        assert!(handle_message(only_notifications).is_none());
+    }
+
+    #[test]
+    fn structured_tools_return_structured_content() {
+        let output = ToolOutput { text: "x".into(), structured: Some(json!({ "worktrees": [] })) };
+        let result = tool_result(Ok(output));
+        assert_eq!(result["content"][0]["text"], "x");
+        assert_eq!(result["structuredContent"], json!({ "worktrees": [] }));
+
+        let plain = tool_result(Ok(String::from("done").into()));
+        assert!(plain.get("structuredContent").is_none());
+    }
+
+    #[test]
+    fn negotiates_requested_version_or_latest() {
+        assert_eq!(negotiate_version(&json!("2025-06-18")), "2025-06-18");
+        assert_eq!(negotiate_version(&json!("2024-11-05")), "2024-11-05");
+        assert_eq!(negotiate_version(&json!("1999-01-01")), "2025-06-18");
+        assert_eq!(negotiate_version(&Value::Null), "2025-06-18");
+    }
+}
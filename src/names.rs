@@ -0,0 +1,82 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+/// Index of worktrees whose directory doesn't follow `<repo>--<branch>`:
+/// ones created with `start --name` and ones brought in with `adopt`.
+/// Stored in the main repo's `.git/workz/names.json` so every worktree sees it.
+pub type NameIndex = BTreeMap<String, Entry>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub branch: String,
+    pub path: PathBuf,
+}
+
+fn index_path(root: &Path) -> Option<PathBuf> {
+    git::common_dir(root)
+        .ok()
+        .map(|dir| dir.join("workz").join("names.json"))
+}
+
+pub fn load(root: &Path) -> NameIndex {
+    let Some(path) = index_path(root) else {
+        return NameIndex::default();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return NameIndex::default();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save(root: &Path, index: &NameIndex) -> Result<()> {
+    let Some(path) = index_path(root) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(index)?)?;
+    Ok(())
+}
+
+/// Remember that the worktree called `name` lives at `path` and holds `branch`.
+pub fn insert(root: &Path, name: &str, branch: &str, path: &Path) -> Result<()> {
+    let mut index = load(root);
+    let entry = Entry { branch: branch.to_string(), path: path.to_path_buf() };
+    index.insert(name.to_string(), entry);
+    save(root, &index)
+}
+
+/// Look up a worktree by the name it was registered under.
+pub fn resolve(root: &Path, name: &str) -> Option<Entry> {
+    load(root).remove(name)
+}
+
+/// Drop any names pointing at the worktree directory `path`.
+pub fn forget_path(root: &Path, path: &Path) -> Result<()> {
+    let mut index = load(root);
+    let before = index.len();
+    index.retain(|_, entry| entry.path != path);
+    if index.len() != before {
+        save(root, &index)?;
+    }
+    Ok(())
+}
+
+/// Point names registered for the worktree at `from` to its new location `to`.
+pub fn relocate(root: &Path, from: &Path, to: &Path) -> Result<()> {
+    let mut index = load(root);
+    let mut changed = false;
+    for entry in index.values_mut().filter(|entry| entry.path == from) {
+        entry.path = to.to_path_buf();
+        changed = true;
+    }
+    if changed {
+        save(root, &index)?;
+    }
+    Ok(())
+}
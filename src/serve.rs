@@ -100,11 +100,11 @@ async fn post_sync(Path(branch): Path<String>) -> impl IntoResponse {
     if !wt_path.exists() {
         return (StatusCode::NOT_FOUND, format!("worktree '{}' not found", branch));
     }
-    let config = match config::load_config(&root) {
+    let config = match config::load_worktree_config(&root, &wt_path) {
         Ok(c) => c,
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
     };
-    match sync::sync_worktree(&root, &wt_path, &config.sync) {
+    match sync::sync_worktree(&root, &wt_path, &config) {
         Ok(_) => (StatusCode::OK, "synced".to_string()),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
     }
@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
-use crate::config::SyncConfig;
+use crate::config::{BootstrapConfig, Config, Hook, SyncConfig};
 
 /// Detected web framework — used by isolation to write framework-specific env vars.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -31,30 +32,119 @@ pub enum Framework {
 
 /// Sync a worktree: symlink heavy directories, copy env files, and auto-install deps.
 /// Returns the detected web framework for use by isolation.
-pub fn sync_worktree(source: &Path, target: &Path, config: &SyncConfig) -> Result<Framework> {
+pub fn sync_worktree(source: &Path, target: &Path, config: &Config) -> Result<Framework> {
+    let sync = &config.sync;
     let project = detect_project(source);
-    symlink_dirs(source, target, &config.symlink, &config.ignore, &project)?;
-    copy_files(source, target, &config.copy, &config.ignore)?;
-    auto_install(source, target, &project)?;
+    if sync.auto_install && sync.install_in_source && sync.install_cmd.is_none() {
+        install_in_source(source, &project, sync);
+    }
+    symlink_dirs(source, target, &sync.symlink, &sync.ignore, &project, sync.resolve_source_symlinks)?;
+    let mut copy = sync.copy.clone();
+    for pattern in project.copy_patterns() {
+        if !copy.iter().any(|p| p == pattern) {
+            copy.push(pattern.to_string());
+        }
+    }
+    copy_files(source, target, &copy, &sync.ignore, sync.copy_gitignored_only)?;
+    if sync.install_tool_versions {
+        install_tool_versions(target);
+    }
+    run_bootstrap(target, &project, &config.bootstrap);
+    if sync.auto_install {
+        match &sync.install_cmd {
+            Some(cmd) => custom_install(cmd, target, sync),
+            None => auto_install(source, target, &project, sync)?,
+        }
+    }
     Ok(project.framework)
 }
 
+/// A language/toolchain `detect_project` recognises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProjectType {
+    Node,
+    Rust,
+    Python,
+    Go,
+    Java,
+    Ruby,
+}
+
+impl std::fmt::Display for ProjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ProjectType::Node => "Node",
+            ProjectType::Rust => "Rust",
+            ProjectType::Python => "Python",
+            ProjectType::Go => "Go",
+            ProjectType::Java => "Java",
+            ProjectType::Ruby => "Ruby",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Detected project types (a repo can be multiple, e.g. Node + Python monorepo).
-#[derive(Default)]
-struct ProjectInfo {
-    has_node: bool,
-    has_rust: bool,
-    has_python: bool,
-    has_go: bool,
-    has_java: bool,
-    framework: Framework,
+#[derive(Debug, Default)]
+pub struct ProjectInfo {
+    pub has_node: bool,
+    pub has_rust: bool,
+    pub has_python: bool,
+    pub has_go: bool,
+    pub has_java: bool,
+    pub has_ruby: bool,
+    /// Cargo workspace root relative to the repo root, whose shared `target`
+    /// gets symlinked. None when the build uses a custom target dir.
+    pub cargo_workspace: Option<PathBuf>,
+    pub framework: Framework,
     /// Detected package manager command for Node projects.
-    node_install_cmd: Option<Vec<String>>,
+    pub node_install_cmd: Option<Vec<String>>,
     /// Detected package manager command for Python projects.
-    python_install_cmd: Option<Vec<String>>,
+    pub python_install_cmd: Option<Vec<String>>,
+}
+
+impl ProjectInfo {
+    /// Every detected type, in a fixed order; empty when nothing is recognised.
+    pub fn types(&self) -> BTreeSet<ProjectType> {
+        [
+            (ProjectType::Node, self.has_node),
+            (ProjectType::Rust, self.has_rust),
+            (ProjectType::Python, self.has_python),
+            (ProjectType::Go, self.has_go),
+            (ProjectType::Java, self.has_java),
+            (ProjectType::Ruby, self.has_ruby),
+        ]
+        .into_iter()
+        .filter_map(|(kind, found)| found.then_some(kind))
+        .collect()
+    }
+
+    /// Local, usually untracked files each detected ecosystem expects, copied
+    /// on top of `sync.copy` (drop any with `sync.ignore`).
+    pub fn copy_patterns(&self) -> Vec<&'static str> {
+        let mut patterns = Vec::new();
+        if self.has_node {
+            patterns.extend([".node-version", ".nvmrc", ".npmrc", ".yarnrc.yml"]);
+        }
+        if self.has_python {
+            patterns.extend([".python-version", "pip.conf"]);
+        }
+        if self.has_go {
+            patterns.extend(["go.work", "go.work.sum"]);
+        }
+        if self.has_java {
+            patterns.extend(["local.properties", "gradle-local.properties"]);
+        }
+        if self.has_ruby {
+            patterns.extend([".ruby-version", ".ruby-gemset", ".bundle/config"]);
+        }
+        patterns
+    }
 }
 
-fn detect_project(root: &Path) -> ProjectInfo {
+/// Sniff `root` for project types, package managers, and the web framework.
+/// File checks only; nothing is executed.
+pub fn detect_project(root: &Path) -> ProjectInfo {
     let mut info = ProjectInfo::default();
 
     // Node.js detection + package manager
@@ -74,9 +164,12 @@ fn detect_project(root: &Path) -> ProjectInfo {
         };
     }
 
-    // Rust
-    if root.join("Cargo.toml").exists() {
+    // Rust — the whole workspace shares one `target` at the workspace root
+    if let Some(workspace) = find_cargo_workspace(root) {
         info.has_rust = true;
+        if !uses_custom_target_dir(&root.join(&workspace)) {
+            info.cargo_workspace = Some(workspace);
+        }
     }
 
     // Python detection + package manager
@@ -111,12 +204,57 @@ fn detect_project(root: &Path) -> ProjectInfo {
         info.has_java = true;
     }
 
+    // Ruby
+    if root.join("Gemfile").exists() {
+        info.has_ruby = true;
+    }
+
     // Framework detection (best-effort, file reads only)
     info.framework = detect_framework(root, &info);
 
     info
 }
 
+/// Find the Cargo project root relative to `root`: the repo root itself if it
+/// has a `Cargo.toml`, otherwise an immediate subdirectory declaring `[workspace]`.
+fn find_cargo_workspace(root: &Path) -> Option<PathBuf> {
+    if root.join("Cargo.toml").exists() {
+        return Some(PathBuf::new());
+    }
+    let mut candidates: Vec<PathBuf> = std::fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .filter(|e| e.path().is_dir() && is_cargo_workspace(&e.path().join("Cargo.toml")))
+        .map(|e| PathBuf::from(e.file_name()))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+fn is_cargo_workspace(manifest: &Path) -> bool {
+    std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .is_some_and(|table| table.contains_key("workspace"))
+}
+
+/// Whether builds write somewhere other than `<workspace>/target`, in which
+/// case there is nothing to symlink.
+fn uses_custom_target_dir(workspace: &Path) -> bool {
+    if std::env::var_os("CARGO_TARGET_DIR").is_some()
+        || std::env::var_os("CARGO_BUILD_TARGET_DIR").is_some()
+    {
+        return true;
+    }
+    [".cargo/config.toml", ".cargo/config"].iter().any(|name| {
+        std::fs::read_to_string(workspace.join(name))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .and_then(|table| table.get("build")?.get("target-dir").cloned())
+            .is_some()
+    })
+}
+
 fn detect_framework(root: &Path, info: &ProjectInfo) -> Framework {
     if info.has_node {
         if let Some(fw) = detect_node_framework(root) {
@@ -224,6 +362,7 @@ fn symlink_dirs(
     dirs: &[String],
     ignore: &[String],
     project: &ProjectInfo,
+    resolve_source: bool,
 ) -> Result<()> {
     for dir_name in dirs {
         if ignore.iter().any(|i| i == dir_name) {
@@ -235,8 +374,18 @@ fn symlink_dirs(
             continue;
         }
 
-        let src = source.join(dir_name);
-        let dst = target.join(dir_name);
+        // Cargo's target lives at the workspace root, which may be a subdirectory
+        let rel = if dir_name == "target" {
+            match &project.cargo_workspace {
+                Some(workspace) => workspace.join(dir_name),
+                None => continue,
+            }
+        } else {
+            PathBuf::from(dir_name)
+        };
+
+        let src = source.join(&rel);
+        let dst = target.join(&rel);
 
         // Only symlink if the source directory actually exists
         if !src.exists() {
@@ -248,31 +397,86 @@ fn symlink_dirs(
             continue;
         }
 
+        // Point straight at the real directory when the source is itself a link
+        // (pnpm store, hoisted monorepo deps) instead of chaining links
+        let src = if resolve_source { src.canonicalize().unwrap_or(src) } else { src };
+
         if let Err(e) = create_symlink(&src, &dst) {
-            eprintln!("  warning: could not symlink {}: {}", dir_name, e);
+            eprintln!("  warning: could not symlink {}: {}", rel.display(), e);
         } else {
-            println!("  symlinked {}", dir_name);
+            println!("  symlinked {}", rel.display());
         }
     }
 
     Ok(())
 }
 
+/// Run the `[bootstrap]` commands for each project type detected in the repo.
+fn run_bootstrap(target: &Path, project: &ProjectInfo, bootstrap: &BootstrapConfig) {
+    let steps = [
+        ("node", project.has_node, &bootstrap.node),
+        ("python", project.has_python, &bootstrap.python),
+        ("rust", project.has_rust, &bootstrap.rust),
+        ("go", project.has_go, &bootstrap.go),
+        ("java", project.has_java, &bootstrap.java),
+    ];
+    for (kind, detected, hook) in steps {
+        if !detected {
+            continue;
+        }
+        for cmd in hook.iter().flat_map(Hook::commands) {
+            println!("  bootstrapping {} ({})...", kind, cmd);
+            let status = std::process::Command::new("sh")
+                .args(["-c", cmd])
+                .current_dir(target)
+                .status();
+            match status {
+                Ok(s) if s.success() => {}
+                Ok(s) => eprintln!("  warning: {} bootstrap exited with {}", kind, s),
+                Err(e) => eprintln!("  warning: could not run {} bootstrap: {}", kind, e),
+            }
+        }
+    }
+}
+
+/// Provision the runtimes pinned by `.mise.toml` / `.tool-versions` so the
+/// right Node/Python is on PATH before dependencies are installed.
+fn install_tool_versions(target: &Path) {
+    let has_mise_config = target.join(".mise.toml").exists() || target.join("mise.toml").exists();
+    let has_tool_versions = target.join(".tool-versions").exists();
+
+    // mise reads .tool-versions too, so prefer it when both managers are around
+    let mut candidates = Vec::new();
+    if has_mise_config || has_tool_versions {
+        candidates.push("mise");
+    }
+    if has_tool_versions {
+        candidates.push("asdf");
+    }
+
+    for tool in candidates {
+        let status = std::process::Command::new(tool)
+            .arg("install")
+            .current_dir(target)
+            .status();
+        match status {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => eprintln!("  warning: could not run {}: {}", tool, e),
+            Ok(s) if s.success() => println!("  tool versions installed ({})", tool),
+            Ok(s) => eprintln!("  warning: {} install exited with {}", tool, s),
+        }
+        return;
+    }
+}
+
 /// Auto-install dependencies if the deps dir doesn't exist in source or target.
-fn auto_install(source: &Path, target: &Path, project: &ProjectInfo) -> Result<()> {
+/// With `install_fallback`, a failed frozen-lockfile install is retried as a plain install.
+fn auto_install(source: &Path, target: &Path, project: &ProjectInfo, sync: &SyncConfig) -> Result<()> {
     // Node: if node_modules doesn't exist anywhere, offer to install
     if project.has_node && !source.join("node_modules").exists() && !target.join("node_modules").exists() {
         if let Some(cmd) = &project.node_install_cmd {
             println!("  installing node dependencies ({})...", cmd[0]);
-            let status = std::process::Command::new(&cmd[0])
-                .args(&cmd[1..])
-                .current_dir(target)
-                .status();
-            match status {
-                Ok(s) if s.success() => println!("  dependencies installed"),
-                Ok(s) => eprintln!("  warning: {} exited with {}", cmd[0], s),
-                Err(e) => eprintln!("  warning: could not run {}: {}", cmd[0], e),
-            }
+            run_install(cmd, target, sync, sync.install_fallback);
         }
     }
 
@@ -285,30 +489,131 @@ fn auto_install(source: &Path, target: &Path, project: &ProjectInfo) -> Result<(
     {
         if let Some(cmd) = &project.python_install_cmd {
             println!("  installing python dependencies ({})...", cmd[0]);
-            let status = std::process::Command::new(&cmd[0])
-                .args(&cmd[1..])
-                .current_dir(target)
-                .status();
-            match status {
-                Ok(s) if s.success() => println!("  dependencies installed"),
-                Ok(s) => eprintln!("  warning: {} exited with {}", cmd[0], s),
-                Err(e) => eprintln!("  warning: could not run {}: {}", cmd[0], e),
-            }
+            run_install(cmd, target, sync, sync.install_fallback);
         }
     }
 
     Ok(())
 }
 
+/// Install into the source checkout when it has no deps yet, so the symlinks
+/// made next point at something and later worktrees share it. Python only
+/// qualifies with uv, the one manager that always puts `.venv` in the project.
+fn install_in_source(source: &Path, project: &ProjectInfo, sync: &SyncConfig) {
+    if project.has_node && !source.join("node_modules").exists() {
+        if let Some(cmd) = &project.node_install_cmd {
+            println!("  installing node dependencies in {} ({})...", source.display(), cmd[0]);
+            run_install(cmd, source, sync, sync.install_fallback);
+        }
+    }
+
+    let no_venv = !source.join(".venv").exists() && !source.join("venv").exists();
+    if project.has_python && no_venv {
+        if let Some(cmd) = project.python_install_cmd.as_ref().filter(|c| c[0] == "uv") {
+            println!("  installing python dependencies in {} ({})...", source.display(), cmd[0]);
+            run_install(cmd, source, sync, sync.install_fallback);
+        }
+    }
+}
+
+/// Run a user-supplied install command through the shell in `target`.
+fn custom_install(cmd: &str, target: &Path, sync: &SyncConfig) {
+    println!("  installing dependencies ({})...", cmd);
+    // Arbitrary commands only get the generic knob
+    let env = sync.install_jobs.map(|n| vec![("CARGO_BUILD_JOBS", n.to_string())]).unwrap_or_default();
+    let mut command = std::process::Command::new("sh");
+    command.args(["-c", cmd]).envs(env).current_dir(target);
+    match run_maybe_quiet(command, sync.quiet_install) {
+        Ok((s, took)) if s.success() => println!("  dependencies installed{}", took),
+        Ok((s, _)) => eprintln!("  warning: install command exited with {}", s),
+        Err(e) => eprintln!("  warning: could not run install command: {}", e),
+    }
+}
+
+/// Run an install command in `target`, warning instead of failing the sync.
+fn run_install(cmd: &[String], target: &Path, sync: &SyncConfig, fallback: bool) {
+    let (limited, env) = match sync.install_jobs {
+        Some(n) => limit_jobs(cmd, n.get()),
+        None => (cmd.to_vec(), Vec::new()),
+    };
+    let mut command = std::process::Command::new(&limited[0]);
+    command.args(&limited[1..]).envs(env).current_dir(target);
+    match run_maybe_quiet(command, sync.quiet_install) {
+        Ok((s, took)) if s.success() => println!("  dependencies installed{}", took),
+        Ok((s, _)) => {
+            eprintln!("  warning: {} exited with {}", cmd[0], s);
+            if let Some(retry) = unfrozen(cmd).filter(|_| fallback) {
+                eprintln!("  warning: lockfile looks out of sync, retrying with `{}`", retry.join(" "));
+                run_install(&retry, target, sync, false);
+            }
+        }
+        Err(e) => eprintln!("  warning: could not run {}: {}", cmd[0], e),
+    }
+}
+
+/// Run `command` with inherited output, or, when `quiet`, capture it and only
+/// replay it (on stderr) if the command fails. Quiet runs also report how long
+/// they took, as " (4.2s)", since there's no output to show progress.
+fn run_maybe_quiet(
+    mut command: std::process::Command,
+    quiet: bool,
+) -> std::io::Result<(std::process::ExitStatus, String)> {
+    if !quiet {
+        return Ok((command.status()?, String::new()));
+    }
+    let started = std::time::Instant::now();
+    let output = command.output()?;
+    if !output.status.success() {
+        use std::io::Write;
+        let mut stderr = std::io::stderr();
+        let _ = stderr.write_all(&output.stdout);
+        let _ = stderr.write_all(&output.stderr);
+    }
+    Ok((output.status, format!(" ({:.1}s)", started.elapsed().as_secs_f64())))
+}
+
+/// Cap a package manager's own concurrency: a flag for managers that take one,
+/// env vars for those that read them, and `CARGO_BUILD_JOBS` for anything that
+/// ends up compiling Rust. Managers without a knob (pip, pipenv) run as-is.
+fn limit_jobs(cmd: &[String], jobs: usize) -> (Vec<String>, Vec<(&'static str, String)>) {
+    let mut cmd = cmd.to_vec();
+    let n = jobs.to_string();
+    let mut env = vec![("CARGO_BUILD_JOBS", n.clone())];
+    match cmd[0].as_str() {
+        "npm" => cmd.push(format!("--maxsockets={n}")),
+        "yarn" | "pnpm" | "bun" => cmd.push(format!("--network-concurrency={n}")),
+        "uv" => {
+            env.push(("UV_CONCURRENT_DOWNLOADS", n.clone()));
+            env.push(("UV_CONCURRENT_INSTALLS", n));
+        }
+        "poetry" => env.push(("POETRY_INSTALLER_MAX_WORKERS", n)),
+        _ => {}
+    }
+    (cmd, env)
+}
+
+/// The non-frozen variant of a lockfile install, if `cmd` is a frozen one.
+fn unfrozen(cmd: &[String]) -> Option<Vec<String>> {
+    if cmd.len() == 2 && cmd[0] == "npm" && cmd[1] == "ci" {
+        return Some(vec!["npm".into(), "install".into()]);
+    }
+    if cmd.iter().any(|arg| arg == "--frozen-lockfile") {
+        return Some(cmd.iter().filter(|arg| *arg != "--frozen-lockfile").cloned().collect());
+    }
+    None
+}
+
 /// Copy files matching glob patterns from source into target.
 fn copy_files(
     source: &Path,
     target: &Path,
     patterns: &[String],
     ignore: &[String],
+    gitignored_only: bool,
 ) -> Result<()> {
+    let mut nested_repos = std::collections::HashSet::new();
     for pattern in patterns {
-        let full_pattern = source.join(pattern);
+        let full_pattern = expand_pattern(source, pattern);
         let pat_str = full_pattern.to_str().unwrap_or("");
 
         let entries = glob::glob(pat_str).context("invalid glob pattern")?;
@@ -319,7 +624,24 @@ fn copy_files(
                 None => continue,
             };
 
-            if ignore.iter().any(|i| i == &file_name) {
+            // Match ignores against the path inside the repo; files pulled in
+            // from outside it (absolute / `~` patterns) only have their name
+            let in_repo = entry.strip_prefix(source).ok();
+            let rel = in_repo.unwrap_or(Path::new(&file_name));
+            if is_ignored(rel, ignore) {
+                continue;
+            }
+
+            // Tracked files already come with the checkout; only carry over
+            // what git ignores (files from outside the repo always qualify)
+            if gitignored_only && in_repo.is_some_and(|rel| !crate::git::is_ignored(source, rel)) {
+                continue;
+            }
+
+            if let Some(repo) = nested_repo(source, &entry) {
+                if nested_repos.insert(repo.clone()) {
+                    eprintln!("  warning: skipping nested git repository {}", repo.display());
+                }
                 continue;
             }
 
@@ -344,6 +666,241 @@ fn copy_files(
     Ok(())
 }
 
+/// Whether `rel`, a path relative to the copy root, is excluded by `ignore`.
+/// Patterns are globs tested against the path and each parent directory, so
+/// `logs` skips everything under `logs/` and `config/**/node_modules` works at
+/// any depth. A pattern without `/` matches any single component (`*.log`).
+fn is_ignored(rel: &Path, ignore: &[String]) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    ignore.iter().any(|raw| {
+        let Ok(pattern) = glob::Pattern::new(raw) else {
+            return false;
+        };
+        if !raw.contains('/') {
+            return rel
+                .components()
+                .any(|c| pattern.matches_with(&c.as_os_str().to_string_lossy(), options));
+        }
+        rel.ancestors()
+            .filter(|a| !a.as_os_str().is_empty())
+            .any(|a| pattern.matches_path_with(a, options))
+    })
+}
+
+/// Resolve a copy pattern: `~/` is the home directory, absolute paths are
+/// used as-is, anything else is relative to the repo root.
+fn expand_pattern(source: &Path, pattern: &str) -> PathBuf {
+    if let Some(rest) = pattern.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    // Joining an absolute path replaces `source` entirely
+    source.join(pattern)
+}
+
+/// If `path` lies inside a git repository nested under `source` (a vendored
+/// repo or submodule checkout), return that repository's relative root.
+fn nested_repo(source: &Path, path: &Path) -> Option<PathBuf> {
+    let rel = path.strip_prefix(source).ok()?;
+    let mut dir = PathBuf::new();
+    for component in rel.parent()?.components() {
+        dir.push(component);
+        if component.as_os_str() == ".git" || source.join(&dir).join(".git").exists() {
+            return Some(dir);
+        }
+    }
+    None
+}
+
+/// Untracked directories larger than this trigger a warning before copying.
+const LARGE_UNTRACKED_DIR: u64 = 100 * 1024 * 1024;
+
+/// Copy every untracked (non-ignored) file from `source` into `target`,
+/// preserving relative paths. Existing files in the target are left alone.
+pub fn copy_untracked(source: &Path, target: &Path) -> Result<()> {
+    let files = crate::git::untracked_files(source)?;
+    if files.is_empty() {
+        println!("  no untracked files to copy");
+        return Ok(());
+    }
+
+    // Warn about large untracked top-level directories
+    let mut dir_sizes: std::collections::BTreeMap<&str, u64> = std::collections::BTreeMap::new();
+    for file in &files {
+        if let Some((top, _)) = file.split_once('/') {
+            let len = std::fs::metadata(source.join(file)).map(|m| m.len()).unwrap_or(0);
+            *dir_sizes.entry(top).or_default() += len;
+        }
+    }
+    for (dir, size) in &dir_sizes {
+        if *size >= LARGE_UNTRACKED_DIR {
+            eprintln!(
+                "  warning: untracked directory {}/ is {} — copying anyway",
+                dir,
+                crate::human_size(*size)
+            );
+        }
+    }
+
+    let mut copied = 0usize;
+    for file in &files {
+        let src = source.join(file);
+        let dst = target.join(file);
+        if !src.is_file() || dst.exists() || nested_repo(source, &src).is_some() {
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if let Err(e) = std::fs::copy(&src, &dst) {
+            eprintln!("  warning: could not copy {}: {}", file, e);
+        } else {
+            copied += 1;
+        }
+    }
+    println!("  copied {} untracked file{}", copied, if copied == 1 { "" } else { "s" });
+
+    Ok(())
+}
+
+/// `clone_ignored` stops copying once this much has been copied.
+const CLONE_IGNORED_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// Replicate every git-ignored file from `source` into `target`. Paths inside
+/// one of the `symlink` directories link the whole directory instead of
+/// copying what's in it; everything else is copied until `CLONE_IGNORED_LIMIT`.
+/// Existing files in the target are left alone.
+pub fn clone_ignored(source: &Path, target: &Path, symlink: &[String]) -> Result<()> {
+    let files = crate::git::ignored_files(source)?;
+    if files.is_empty() {
+        println!("  no ignored files to clone");
+        return Ok(());
+    }
+
+    let mut linked: Vec<PathBuf> = Vec::new();
+    let (mut copied, mut bytes, mut skipped) = (0usize, 0u64, 0usize);
+    for file in &files {
+        let rel = Path::new(file);
+        if linked.iter().any(|dir| rel.starts_with(dir)) {
+            continue;
+        }
+
+        // The shallowest symlink-set directory on the path, e.g. `web/node_modules`
+        let link_dir = rel
+            .ancestors()
+            .skip(1)
+            .filter(|dir| dir.file_name().is_some_and(|n| symlink.iter().any(|s| n == s.as_str())))
+            .last();
+        if let Some(dir) = link_dir {
+            let dst = target.join(dir);
+            if dst.symlink_metadata().is_err() {
+                if let Some(parent) = dst.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                match create_symlink(&source.join(dir), &dst) {
+                    Ok(()) => println!("  symlinked {}", dir.display()),
+                    Err(e) => eprintln!("  warning: could not symlink {}: {}", dir.display(), e),
+                }
+            }
+            linked.push(dir.to_path_buf());
+            continue;
+        }
+
+        let src = source.join(rel);
+        let dst = target.join(rel);
+        if !src.is_file() || dst.exists() || nested_repo(source, &src).is_some() {
+            continue;
+        }
+        let len = std::fs::metadata(&src).map(|m| m.len()).unwrap_or(0);
+        if bytes + len > CLONE_IGNORED_LIMIT {
+            skipped += 1;
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if let Err(e) = std::fs::copy(&src, &dst) {
+            eprintln!("  warning: could not copy {}: {}", file, e);
+        } else {
+            copied += 1;
+            bytes += len;
+        }
+    }
+    println!(
+        "  cloned {} ignored file{} ({})",
+        copied,
+        if copied == 1 { "" } else { "s" },
+        crate::human_size(bytes)
+    );
+    if skipped > 0 {
+        eprintln!(
+            "  warning: skipped {} ignored file{} past the {} limit",
+            skipped,
+            if skipped == 1 { "" } else { "s" },
+            crate::human_size(CLONE_IGNORED_LIMIT)
+        );
+    }
+
+    Ok(())
+}
+
+/// `backup_untracked` stops copying once this much has been copied.
+const BACKUP_UNTRACKED_LIMIT: u64 = 512 * 1024 * 1024;
+
+/// Copy the worktree's untracked and ignored files into `dest`, keeping their
+/// repo-relative paths. Symlinks and anything under one of the `skip`
+/// directories (the heavy, reinstallable ones workz symlinks) are left out, as
+/// are nested repos. Returns how many files were backed up.
+pub fn backup_untracked(worktree: &Path, dest: &Path, skip: &[String]) -> Result<usize> {
+    let mut files = crate::git::untracked_files(worktree)?;
+    files.extend(crate::git::ignored_files(worktree)?);
+    files.sort();
+    files.dedup();
+
+    let (mut copied, mut bytes, mut skipped) = (0usize, 0u64, 0usize);
+    for file in &files {
+        let rel = Path::new(file);
+        if rel.ancestors().skip(1).any(|dir| dir.file_name().is_some_and(|n| skip.iter().any(|s| n == s.as_str()))) {
+            continue;
+        }
+        let src = worktree.join(rel);
+        let Ok(meta) = src.symlink_metadata() else { continue };
+        if !meta.is_file() || nested_repo(worktree, &src).is_some() {
+            continue;
+        }
+        if bytes + meta.len() > BACKUP_UNTRACKED_LIMIT {
+            skipped += 1;
+            continue;
+        }
+        let dst = dest.join(rel);
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if let Err(e) = std::fs::copy(&src, &dst) {
+            eprintln!("  warning: could not back up {}: {}", file, e);
+        } else {
+            copied += 1;
+            bytes += meta.len();
+        }
+    }
+    if skipped > 0 {
+        eprintln!(
+            "  warning: {} untracked file{} past the {} backup limit not saved",
+            skipped,
+            if skipped == 1 { "" } else { "s" },
+            crate::human_size(BACKUP_UNTRACKED_LIMIT)
+        );
+    }
+    if copied > 0 {
+        println!("  backed up {} untracked file{} ({}) to {}", copied, if copied == 1 { "" } else { "s" }, crate::human_size(bytes), dest.display());
+    }
+    Ok(copied)
+}
+
 /// Create a symbolic link (Unix) or directory junction (Windows).
 fn create_symlink(src: &Path, dst: &Path) -> Result<()> {
     #[cfg(unix)]
@@ -369,3 +926,269 @@ fn create_symlink(src: &Path, dst: &Path) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("workz-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn cargo_workspace_in_subdirectory() {
+        let root = temp_dir("cargo-ws");
+        std::fs::create_dir_all(root.join("rust/crates/core")).unwrap();
+        std::fs::write(root.join("rust/Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        std::fs::write(root.join("rust/crates/core/Cargo.toml"), "[package]\nname = \"core\"\n").unwrap();
+
+        assert_eq!(find_cargo_workspace(&root), Some(PathBuf::from("rust")));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn detect_project_reports_types_and_install_cmds() {
+        let root = temp_dir("detect-types");
+        std::fs::write(root.join("package.json"), "{}").unwrap();
+        std::fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(root.join("pyproject.toml"), "").unwrap();
+        std::fs::write(root.join("uv.lock"), "").unwrap();
+
+        let info = detect_project(&root);
+        assert_eq!(info.types().into_iter().collect::<Vec<_>>(), [ProjectType::Node, ProjectType::Python]);
+        assert_eq!(info.node_install_cmd.as_ref().unwrap()[0], "pnpm");
+        assert_eq!(info.python_install_cmd.as_deref().unwrap(), ["uv", "sync"]);
+        assert!(info.copy_patterns().contains(&".nvmrc"));
+        assert!(info.copy_patterns().contains(&".python-version"));
+        assert!(!info.copy_patterns().contains(&".ruby-version"));
+        assert!(detect_project(&root.join("missing")).types().is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn cargo_workspace_at_root() {
+        let root = temp_dir("cargo-root");
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = []\n").unwrap();
+
+        assert_eq!(find_cargo_workspace(&root), Some(PathBuf::new()));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn cargo_custom_target_dir_from_config() {
+        let root = temp_dir("cargo-target-dir");
+        std::fs::create_dir_all(root.join(".cargo")).unwrap();
+        std::fs::write(root.join(".cargo/config.toml"), "[build]\ntarget-dir = \"/tmp/shared\"\n").unwrap();
+
+        assert!(uses_custom_target_dir(&root));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_skips_nested_git_repos() {
+        let root = temp_dir("nested-git");
+        let (source, target) = (root.join("src"), root.join("dst"));
+        std::fs::create_dir_all(source.join("vendor/lib/.git/objects")).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(source.join("app.env"), "A=1").unwrap();
+        std::fs::write(source.join("vendor/lib/lib.env"), "B=2").unwrap();
+        std::fs::write(source.join("vendor/lib/.git/objects/pack"), "x").unwrap();
+
+        let patterns = vec!["**/*.env".to_string(), "**/pack".to_string()];
+        copy_files(&source, &target, &patterns, &[], false).unwrap();
+
+        assert!(target.join("app.env").exists());
+        assert!(!target.join("lib.env").exists());
+        assert!(!target.join("pack").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_patterns_expand_home_and_absolute_paths() {
+        let source = Path::new("/repo");
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(expand_pattern(source, ".env*"), PathBuf::from("/repo/.env*"));
+        assert_eq!(expand_pattern(source, "~/.npmrc"), home.join(".npmrc"));
+        assert_eq!(expand_pattern(source, "/etc/app/*.pem"), PathBuf::from("/etc/app/*.pem"));
+    }
+
+    #[test]
+    fn ignore_globs_match_relative_paths() {
+        let ignore = vec!["*.log".to_string(), "config/**/node_modules".to_string(), "tmp".to_string()];
+
+        assert!(is_ignored(Path::new("debug.log"), &ignore));
+        assert!(is_ignored(Path::new("deep/nested/app.log"), &ignore));
+        assert!(is_ignored(Path::new("config/node_modules/x.json"), &ignore));
+        assert!(is_ignored(Path::new("config/a/b/node_modules/x.json"), &ignore));
+        assert!(is_ignored(Path::new("tmp/cache/.env"), &ignore));
+
+        assert!(!is_ignored(Path::new("node_modules/x.json"), &ignore));
+        assert!(!is_ignored(Path::new("config/app.json"), &ignore));
+        assert!(!is_ignored(Path::new("logs/app.txt"), &ignore));
+    }
+
+    #[test]
+    fn copy_skips_nested_ignored_entries() {
+        let root = temp_dir("nested-ignore");
+        let (source, target) = (root.join("src"), root.join("dst"));
+        std::fs::create_dir_all(source.join("config/web/node_modules")).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(source.join("config/web/settings.json"), "{}").unwrap();
+        std::fs::write(source.join("config/web/node_modules/pkg.json"), "{}").unwrap();
+
+        let patterns = vec!["config/**/*.json".to_string()];
+        let ignore = vec!["config/**/node_modules".to_string()];
+        copy_files(&source, &target, &patterns, &ignore, false).unwrap();
+
+        assert!(target.join("settings.json").exists());
+        assert!(!target.join("pkg.json").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copy_gitignored_only_skips_tracked_matches() {
+        let root = temp_dir("gitignored-only");
+        let (source, target) = (root.join("src"), root.join("dst"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        let git = |args: &[&str]| {
+            let ok = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&source)
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        std::fs::write(source.join(".gitignore"), ".env.local\n").unwrap();
+        std::fs::write(source.join(".env.example"), "A=").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        std::fs::write(source.join(".env.local"), "A=secret").unwrap();
+
+        copy_files(&source, &target, &[".env*".to_string()], &[], true).unwrap();
+
+        assert!(target.join(".env.local").exists());
+        assert!(!target.join(".env.example").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clone_ignored_copies_files_and_links_symlink_dirs() {
+        let root = temp_dir("clone-ignored");
+        let (source, target) = (root.join("src"), root.join("dst"));
+        std::fs::create_dir_all(source.join("web/node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(source.join("db")).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        let ok = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&source)
+            .status()
+            .unwrap()
+            .success();
+        assert!(ok);
+        std::fs::write(source.join(".gitignore"), "node_modules/\ndb/\n").unwrap();
+        std::fs::write(source.join("web/node_modules/pkg/index.js"), "").unwrap();
+        std::fs::write(source.join("db/dev.sqlite"), "data").unwrap();
+
+        clone_ignored(&source, &target, &["node_modules".to_string()]).unwrap();
+
+        assert_eq!(std::fs::read_to_string(target.join("db/dev.sqlite")).unwrap(), "data");
+        let link = target.join("web/node_modules");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_link(&link).unwrap(), source.join("web/node_modules"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backup_untracked_skips_dependency_dirs_and_links() {
+        let root = temp_dir("backup-untracked");
+        let (worktree, dest) = (root.join("wt"), root.join("backup"));
+        std::fs::create_dir_all(worktree.join("node_modules/pkg")).unwrap();
+        let ok = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&worktree)
+            .status()
+            .unwrap()
+            .success();
+        assert!(ok);
+        std::fs::write(worktree.join(".gitignore"), "node_modules/\n.env\n").unwrap();
+        std::fs::write(worktree.join(".env"), "SECRET=1").unwrap();
+        std::fs::write(worktree.join("notes.md"), "todo").unwrap();
+        std::fs::write(worktree.join("node_modules/pkg/index.js"), "").unwrap();
+        std::os::unix::fs::symlink(worktree.join("notes.md"), worktree.join("link.md")).unwrap();
+
+        let copied = backup_untracked(&worktree, &dest, &["node_modules".to_string()]).unwrap();
+
+        assert_eq!(copied, 3); // .env, .gitignore, notes.md
+        assert_eq!(std::fs::read_to_string(dest.join(".env")).unwrap(), "SECRET=1");
+        assert!(dest.join("notes.md").is_file());
+        assert!(!dest.join("node_modules").exists());
+        assert!(!dest.join("link.md").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn limit_jobs_maps_to_manager_knobs() {
+        let cmd = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        let (npm, env) = limit_jobs(&cmd(&["npm", "ci"]), 2);
+        assert_eq!(npm, cmd(&["npm", "ci", "--maxsockets=2"]));
+        assert_eq!(env, [("CARGO_BUILD_JOBS", "2".to_string())]);
+
+        let (pnpm, _) = limit_jobs(&cmd(&["pnpm", "install", "--frozen-lockfile"]), 4);
+        assert_eq!(pnpm.last().unwrap(), "--network-concurrency=4");
+
+        let (uv, env) = limit_jobs(&cmd(&["uv", "sync"]), 3);
+        assert_eq!(uv, cmd(&["uv", "sync"]));
+        assert!(env.contains(&("UV_CONCURRENT_INSTALLS", "3".to_string())));
+
+        let (pip, _) = limit_jobs(&cmd(&["pip", "install", "-r", "requirements.txt"]), 1);
+        assert_eq!(pip, cmd(&["pip", "install", "-r", "requirements.txt"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_resolves_linked_source_dir() {
+        let root = temp_dir("resolve-source");
+        let (source, target, store) = (root.join("src"), root.join("dst"), root.join("store"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::create_dir_all(&store).unwrap();
+        std::os::unix::fs::symlink(&store, source.join("node_modules")).unwrap();
+
+        let project = ProjectInfo { has_node: true, ..Default::default() };
+        let dirs = vec!["node_modules".to_string()];
+
+        symlink_dirs(&source, &target, &dirs, &[], &project, true).unwrap();
+        let link = std::fs::read_link(target.join("node_modules")).unwrap();
+        assert_eq!(link, store.canonicalize().unwrap());
+
+        std::fs::remove_file(target.join("node_modules")).unwrap();
+        symlink_dirs(&source, &target, &dirs, &[], &project, false).unwrap();
+        let link = std::fs::read_link(target.join("node_modules")).unwrap();
+        assert_eq!(link, source.join("node_modules"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn unfrozen_drops_lockfile_strictness() {
+        let cmd = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(unfrozen(&cmd(&["npm", "ci"])), Some(cmd(&["npm", "install"])));
+        assert_eq!(
+            unfrozen(&cmd(&["pnpm", "install", "--frozen-lockfile"])),
+            Some(cmd(&["pnpm", "install"]))
+        );
+        assert_eq!(unfrozen(&cmd(&["uv", "sync"])), None);
+    }
+}
@@ -357,18 +357,14 @@ impl DashboardApp {
 
     fn nav_up(&mut self) {
         match self.panel {
-            Panel::Worktrees => {
-                if !self.worktrees.is_empty() {
-                    self.wt_selected = self.wt_selected.saturating_sub(1);
-                    self.wt_table.select(Some(self.wt_selected));
-                    self.refresh_files();
-                }
+            Panel::Worktrees if !self.worktrees.is_empty() => {
+                self.wt_selected = self.wt_selected.saturating_sub(1);
+                self.wt_table.select(Some(self.wt_selected));
+                self.refresh_files();
             }
-            Panel::Fleet => {
-                if !self.fleet_rows.is_empty() {
-                    self.fleet_selected = self.fleet_selected.saturating_sub(1);
-                    self.fleet_table.select(Some(self.fleet_selected));
-                }
+            Panel::Fleet if !self.fleet_rows.is_empty() => {
+                self.fleet_selected = self.fleet_selected.saturating_sub(1);
+                self.fleet_table.select(Some(self.fleet_selected));
             }
             _ => {}
         }
@@ -376,18 +372,14 @@ impl DashboardApp {
 
     fn nav_down(&mut self) {
         match self.panel {
-            Panel::Worktrees => {
-                if !self.worktrees.is_empty() {
-                    self.wt_selected = (self.wt_selected + 1).min(self.worktrees.len() - 1);
-                    self.wt_table.select(Some(self.wt_selected));
-                    self.refresh_files();
-                }
+            Panel::Worktrees if !self.worktrees.is_empty() => {
+                self.wt_selected = (self.wt_selected + 1).min(self.worktrees.len() - 1);
+                self.wt_table.select(Some(self.wt_selected));
+                self.refresh_files();
             }
-            Panel::Fleet => {
-                if !self.fleet_rows.is_empty() {
-                    self.fleet_selected = (self.fleet_selected + 1).min(self.fleet_rows.len() - 1);
-                    self.fleet_table.select(Some(self.fleet_selected));
-                }
+            Panel::Fleet if !self.fleet_rows.is_empty() => {
+                self.fleet_selected = (self.fleet_selected + 1).min(self.fleet_rows.len() - 1);
+                self.fleet_table.select(Some(self.fleet_selected));
             }
             _ => {}
         }
@@ -466,8 +458,9 @@ fn dashboard_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<(
                                 if !wt.is_bare {
                                     let path = wt.path.clone();
                                     if let Some(root) = &app.repo_root {
-                                        let config = config::load_config(root).unwrap_or_default();
-                                        match sync::sync_worktree(root, &path, &config.sync) {
+                                        let config = config::load_worktree_config(root, &path)
+                                            .unwrap_or_default();
+                                        match sync::sync_worktree(root, &path, &config) {
                                             Ok(_) => app.set_status("synced"),
                                             Err(e) => app.set_status(format!("sync error: {e}")),
                                         }
@@ -550,8 +543,8 @@ fn handle_input_action(app: &mut DashboardApp, action: InputAction, input: &str)
                 }
                 match git::worktree_add(&wt_path, branch, None) {
                     Ok(_) => {
-                        let config = config::load_config(root).unwrap_or_default();
-                        let _ = sync::sync_worktree(root, &wt_path, &config.sync);
+                        let config = config::load_worktree_config(root, &wt_path).unwrap_or_default();
+                        let _ = sync::sync_worktree(root, &wt_path, &config);
                         app.set_status(format!("created '{}'", branch));
                         app.refresh();
                     }